@@ -0,0 +1,207 @@
+use futures_util::stream::{self, Stream};
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use crate::constants;
+use crate::entities::authenticate_response::AuthenticateResponse;
+use crate::entities::cancel_order_reject_event::CancelOrderRejectEvent;
+use crate::entities::open_order::OpenOrder;
+use crate::entities::order_trade_event::OrderTradeEvent;
+use crate::entities::position::Position;
+use crate::entities::ticket_update::TicketUpdate;
+use crate::error::NdaxError;
+use crate::frame::{Frame, MessageType};
+use crate::order_manager::OrderManager;
+use crate::ws_client::WsClient;
+
+/// An account event pushed by the gateway after SubscribeAccountEvents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountEvent {
+    OrderState(OpenOrder),
+    Trade(OrderTradeEvent),
+    CancelOrderReject(CancelOrderRejectEvent),
+    Position(Position),
+    Deposit(TicketUpdate),
+    Withdrawal(TicketUpdate),
+    // Account events the crate has no typed form for yet, e.g. NewOrderRejectEvent
+    Other(Frame),
+    // The consumer fell behind and this many frames were dropped; local state
+    // built from events should be reconciled
+    Missed(u64),
+}
+
+impl AccountEvent {
+    /// Decodes an account event frame, or returns `None` for any other frame.
+    pub fn from_frame(frame: &Frame) -> Option<Result<Self, NdaxError>> {
+        if frame.message_type != MessageType::Event {
+            return None;
+        }
+        let event = match frame.function_name.as_str() {
+            constants::ORDER_STATE_EVENT => frame.payload_as().map(AccountEvent::OrderState),
+            constants::ORDER_TRADE_EVENT => frame.payload_as().map(AccountEvent::Trade),
+            constants::CANCEL_ORDER_REJECT_EVENT => {
+                frame.payload_as().map(AccountEvent::CancelOrderReject)
+            }
+            constants::ACCOUNT_POSITION_EVENT => frame.payload_as().map(AccountEvent::Position),
+            constants::DEPOSIT_TICKET_UPDATE_EVENT => frame.payload_as().map(AccountEvent::Deposit),
+            constants::WITHDRAW_TICKET_UPDATE_EVENT => {
+                frame.payload_as().map(AccountEvent::Withdrawal)
+            }
+            constants::NEW_ORDER_REJECT_EVENT
+            | constants::CANCEL_REPLACE_ORDER_REJECT_EVENT
+            | constants::CANCEL_ALL_ORDERS_REJECT_EVENT
+            | constants::TRANSACTION_EVENT => Ok(AccountEvent::Other(frame.clone())),
+            // Market data and anything else sharing the connection is not about the account
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+/// An authenticated gateway connection subscribed to one account's events.
+///
+/// Signs in through the order manager's `AuthSession`, so REST and WebSocket
+/// use the same credentials. After a reconnect the client signs in again and
+/// renews the account subscription before anything else.
+pub struct AccountSession {
+    client: WsClient,
+    account_id: u64,
+    authentication: AuthenticateResponse,
+}
+
+impl AccountSession {
+    pub async fn start(client: WsClient, order_manager: &OrderManager) -> Result<Self, NdaxError> {
+        let account_id = order_manager.parse_account_id()?;
+        let authentication = order_manager.auth().authenticate_ws(&client).await?;
+
+        client
+            .subscribe(
+                constants::SUBSCRIBE_ACCOUNT_EVENTS,
+                json!({ "OMSId": 1, "AccountId": account_id }),
+            )
+            .await?;
+
+        Ok(AccountSession {
+            client,
+            account_id,
+            authentication,
+        })
+    }
+
+    pub fn client(&self) -> &WsClient {
+        &self.client
+    }
+
+    pub fn account_id(&self) -> u64 {
+        self.account_id
+    }
+
+    pub fn authentication(&self) -> &AuthenticateResponse {
+        &self.authentication
+    }
+
+    // A new receiver of account events from this point on
+    pub fn events(&self) -> AccountEvents {
+        AccountEvents {
+            receiver: self.client.events(),
+        }
+    }
+}
+
+/// Receives the account events of a session, skipping unrelated frames.
+pub struct AccountEvents {
+    receiver: broadcast::Receiver<Frame>,
+}
+
+impl AccountEvents {
+    /// Waits for the next account event.
+    ///
+    /// A payload that fails to decode is returned as an error without ending
+    /// the feed; `NdaxError::ConnectionClosed` means the client is gone.
+    pub async fn recv(&mut self) -> Result<AccountEvent, NdaxError> {
+        loop {
+            let frame = match self.receiver.recv().await {
+                Ok(frame) => frame,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    return Ok(AccountEvent::Missed(skipped))
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    return Err(NdaxError::ConnectionClosed)
+                }
+            };
+            if let Some(event) = AccountEvent::from_frame(&frame) {
+                return event;
+            }
+        }
+    }
+
+    // Adapts the receiver to a Stream that ends when the client is dropped
+    pub fn into_stream(self) -> impl Stream<Item = Result<AccountEvent, NdaxError>> {
+        stream::unfold(self, |mut events| async move {
+            match events.recv().await {
+                Err(NdaxError::ConnectionClosed) => None,
+                event => Some((event, events)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn event(function_name: &str, payload: Value) -> Frame {
+        Frame::new(MessageType::Event, 0, function_name, payload)
+    }
+
+    #[test]
+    fn test_decodes_account_events() {
+        let position = event(
+            "AccountPositionEvent",
+            json!({"OMSId":1,"AccountId":185,"ProductSymbol":"CAD","ProductId":3,
+                   "Amount":1000.0,"Hold":250.0,"PendingDeposits":0,"PendingWithdraws":0,
+                   "TotalDayDeposits":0,"TotalDayWithdraws":0}),
+        );
+        assert!(matches!(
+            AccountEvent::from_frame(&position),
+            Some(Ok(AccountEvent::Position(position))) if position.product_symbol == "CAD"
+        ));
+
+        let withdrawal = event(
+            "WithdrawTicketUpdateEvent",
+            json!({"AccountId":185,"AssetId":1,"AssetName":"Bitcoin","Amount":0.1,
+                   "Status":"Pending","TicketNumber":77}),
+        );
+        assert!(matches!(
+            AccountEvent::from_frame(&withdrawal),
+            Some(Ok(AccountEvent::Withdrawal(ticket))) if ticket.ticket_number == 77
+        ));
+
+        let malformed = event("OrderTradeEvent", json!({"TradeId":"x"}));
+        assert!(matches!(AccountEvent::from_frame(&malformed), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_ignores_replies_and_market_data() {
+        let reply = Frame::new(
+            MessageType::Reply,
+            4,
+            "SubscribeAccountEvents",
+            json!({"Subscribed":true}),
+        );
+        assert!(AccountEvent::from_frame(&reply).is_none());
+        let trades = event("TradeDataUpdateEvent", json!([]));
+        assert!(AccountEvent::from_frame(&trades).is_none());
+        let ticker = event("Level1UpdateEvent", json!({"InstrumentId":1}));
+        assert!(AccountEvent::from_frame(&ticker).is_none());
+        let market_state = event("MarketStateUpdate", json!({"Action":"Pause"}));
+        assert!(AccountEvent::from_frame(&market_state).is_none());
+
+        let rejected = event("NewOrderRejectEvent", json!({"OrderId":0}));
+        assert!(matches!(
+            AccountEvent::from_frame(&rejected),
+            Some(Ok(AccountEvent::Other(_)))
+        ));
+    }
+}
@@ -0,0 +1,417 @@
+use hmac::NewMac;
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+use crate::constants;
+use crate::entities::authenticate_response::AuthenticateResponse;
+use crate::error::NdaxError;
+use crate::rest::parse_response;
+use crate::ws_client::WsClient;
+
+// Type alias for the HMAC-SHA256 algorithm
+type HmacSha256 = Hmac<Sha256>;
+type TwoFactorCode = Arc<dyn Fn() -> String + Send + Sync>;
+
+// NDAX does not report how long a session lasts, so tokens are renewed well
+// before the gateway drops idle sessions
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(60 * 60);
+const SESSION_TOKEN_HEADER: &str = "aptoken";
+const PENDING_2FA_TOKEN_HEADER: &str = "pending2FaToken";
+
+/// API key credentials used to sign AuthenticateUser requests.
+///
+/// Clones share the nonce counter, so every signature they produce uses a
+/// nonce larger than the last.
+#[derive(Clone)]
+pub struct Credentials {
+    api_key: String,
+    secret: String,
+    user_id: String,
+    last_nonce: Arc<AtomicU64>,
+}
+
+impl Credentials {
+    pub fn new(api_key: &str, secret: &str, user_id: &str) -> Self {
+        Credentials {
+            api_key: api_key.to_string(),
+            secret: secret.to_string(),
+            user_id: user_id.to_string(),
+            last_nonce: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    // The signed Nonce, APIKey, Signature and UserId fields of AuthenticateUser
+    pub fn auth_dict(&self) -> HashMap<&'static str, String> {
+        let nonce = self.next_nonce().to_string();
+        let raw_signature = format!("{}{}{}", nonce, self.user_id, self.api_key);
+
+        let mut mac: Hmac<Sha256> = HmacSha256::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(raw_signature.as_bytes());
+
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        let mut auth_info = HashMap::new();
+        auth_info.insert("Nonce", nonce);
+        auth_info.insert("APIKey", self.api_key.clone());
+        auth_info.insert("Signature", signature);
+        auth_info.insert("UserId", self.user_id.clone());
+
+        auth_info
+    }
+
+    // The AuthenticateUser payload for the WebSocket gateway
+    pub fn auth_payload(&self) -> Value {
+        json!(self.auth_dict())
+    }
+
+    // The AuthenticateUser headers for the REST API
+    pub fn auth_headers(&self) -> Result<HeaderMap, NdaxError> {
+        let mut headers = HeaderMap::new();
+        for (key, value) in self.auth_dict() {
+            insert_header(&mut headers, key, &value)?;
+        }
+        Ok(headers)
+    }
+
+    // Milliseconds since the epoch, bumped when two requests land in the same millisecond
+    fn next_nonce(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as u64;
+        let mut last = self.last_nonce.load(Ordering::Relaxed);
+        loop {
+            let next = now.max(last + 1);
+            match self.last_nonce.compare_exchange_weak(
+                last,
+                next,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return next,
+                Err(current) => last = current,
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("api_key", &self.api_key)
+            .field("user_id", &self.user_id)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    token: String,
+    issued_at: Instant,
+}
+
+/// Signs in to NDAX once and hands out the session token to private calls.
+///
+/// Handles are cheap to clone and share one cached token. The token is renewed
+/// when it reaches its lifetime or when a call reports it was rejected; if the
+/// account uses 2FA, the code provider is asked for a code on every sign-in.
+#[derive(Clone)]
+pub struct AuthSession {
+    api_url: String,
+    credentials: Credentials,
+    client: Client,
+    token_lifetime: Duration,
+    two_factor_code: Option<TwoFactorCode>,
+    cached: Arc<Mutex<Option<CachedToken>>>,
+}
+
+impl AuthSession {
+    pub fn new(api_url: &str, credentials: Credentials) -> Self {
+        AuthSession {
+            api_url: api_url.to_string(),
+            credentials,
+            client: Client::new(),
+            token_lifetime: DEFAULT_TOKEN_LIFETIME,
+            two_factor_code: None,
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn with_token_lifetime(mut self, token_lifetime: Duration) -> Self {
+        self.token_lifetime = token_lifetime;
+        self
+    }
+
+    // Sets where Authenticate2FA codes come from, e.g. a TOTP generator
+    pub fn with_two_factor<F>(mut self, two_factor_code: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        self.two_factor_code = Some(Arc::new(two_factor_code));
+        self
+    }
+
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    // The cached session token, signing in first if there is none or it expired
+    pub async fn session_token(&self) -> Result<String, NdaxError> {
+        let mut cached = self.cached.lock().await;
+        if let Some(token) = cached.as_ref() {
+            if token.issued_at.elapsed() < self.token_lifetime {
+                return Ok(token.token.clone());
+            }
+        }
+        let response = self.sign_in().await?;
+        let token = session_token_of(&response)?;
+        *cached = Some(CachedToken {
+            token: token.clone(),
+            issued_at: Instant::now(),
+        });
+        Ok(token)
+    }
+
+    // Signs in again now, replacing any cached token
+    pub async fn authenticate(&self) -> Result<AuthenticateResponse, NdaxError> {
+        let mut cached = self.cached.lock().await;
+        let response = self.sign_in().await?;
+        *cached = Some(CachedToken {
+            token: session_token_of(&response)?,
+            issued_at: Instant::now(),
+        });
+        Ok(response)
+    }
+
+    // Drops the cached token so the next call signs in again
+    pub async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+
+    // Headers for a private REST call: the session token as aptoken
+    pub async fn headers(&self) -> Result<HeaderMap, NdaxError> {
+        let token = self.session_token().await?;
+        let mut headers = HeaderMap::new();
+        insert_header(&mut headers, SESSION_TOKEN_HEADER, &token)?;
+        Ok(headers)
+    }
+
+    /// Signs a WebSocket connection in with the same credentials.
+    ///
+    /// The client re-sends a freshly signed AuthenticateUser after every
+    /// reconnect, and answers a 2FA challenge with a new code each time.
+    pub async fn authenticate_ws(
+        &self,
+        client: &WsClient,
+    ) -> Result<AuthenticateResponse, NdaxError> {
+        let credentials = self.credentials.clone();
+        let reply = client
+            .call(
+                constants::AUTHENTICATE_USER_PATH_URL,
+                credentials.auth_payload(),
+            )
+            .await?;
+        let mut response: AuthenticateResponse = serde_json::from_value(reply)?;
+        if response.requires_2fa {
+            let code = self.two_factor_code()?;
+            let reply = client
+                .call(
+                    constants::AUTHENTICATE_2FA_PATH_URL,
+                    json!({ "Code": code }),
+                )
+                .await?;
+            response = serde_json::from_value(reply)?;
+        }
+        let response = check_authenticated(response)?;
+        client.set_authenticator(move || credentials.auth_payload());
+        if let Some(two_factor_code) = self.two_factor_code.clone() {
+            client.set_two_factor_code(move || two_factor_code());
+        }
+        Ok(response)
+    }
+
+    // AuthenticateUser over REST, followed by Authenticate2FA when the account asks for it
+    async fn sign_in(&self) -> Result<AuthenticateResponse, NdaxError> {
+        let url = format!("{}{}", self.api_url, constants::AUTHENTICATE_USER_PATH_URL);
+        let response = self
+            .client
+            .get(&url)
+            .headers(self.credentials.auth_headers()?)
+            .send()
+            .await?;
+        let response = parse_response::<AuthenticateResponse>(response).await?;
+        if !response.requires_2fa {
+            return check_authenticated(response);
+        }
+
+        let mut headers = HeaderMap::new();
+        if let Some(pending_token) = response.two_fa_token.as_deref() {
+            insert_header(&mut headers, PENDING_2FA_TOKEN_HEADER, pending_token)?;
+        }
+        let url = format!("{}{}", self.api_url, constants::AUTHENTICATE_2FA_PATH_URL);
+        let response = self
+            .client
+            .post(&url)
+            .headers(headers)
+            .json(&json!({ "Code": self.two_factor_code()? }))
+            .send()
+            .await?;
+        check_authenticated(parse_response::<AuthenticateResponse>(response).await?)
+    }
+
+    fn two_factor_code(&self) -> Result<String, NdaxError> {
+        match &self.two_factor_code {
+            Some(two_factor_code) => Ok(two_factor_code()),
+            None => Err(NdaxError::Authentication(
+                "account requires 2FA but no code provider is set".to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for AuthSession {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AuthSession")
+            .field("api_url", &self.api_url)
+            .field("credentials", &self.credentials)
+            .field("token_lifetime", &self.token_lifetime)
+            .finish()
+    }
+}
+
+// Turns a reply that did not sign us in into an error
+pub(crate) fn check_authenticated(
+    response: AuthenticateResponse,
+) -> Result<AuthenticateResponse, NdaxError> {
+    if response.authenticated {
+        Ok(response)
+    } else {
+        Err(NdaxError::Authentication(
+            response.errormsg.unwrap_or_default(),
+        ))
+    }
+}
+
+fn session_token_of(response: &AuthenticateResponse) -> Result<String, NdaxError> {
+    response
+        .session_token
+        .clone()
+        .ok_or_else(|| NdaxError::Authentication("no session token in reply".to_string()))
+}
+
+fn insert_header(headers: &mut HeaderMap, key: &str, value: &str) -> Result<(), NdaxError> {
+    let name = HeaderName::from_str(key)
+        .map_err(|_| NdaxError::Config(format!("invalid header name {}", key)))?;
+    let value = HeaderValue::from_str(value)
+        .map_err(|_| NdaxError::Config(format!("invalid value for header {}", key)))?;
+    headers.insert(name, value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::canned_server;
+    use std::sync::atomic::AtomicU32;
+
+    fn signed_in(token: &str) -> (u16, Value) {
+        (200, json!({"Authenticated": true, "SessionToken": token}))
+    }
+
+    fn needs_2fa() -> (u16, Value) {
+        (
+            200,
+            json!({"Authenticated": false, "Requires2FA": true, "TwoFAType": "Google",
+                   "TwoFAToken": "pending"}),
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_is_renewed_after_its_lifetime() {
+        let (api_url, requests) =
+            canned_server(vec![signed_in("first"), signed_in("second")]).await;
+        let session = AuthSession::new(&api_url, Credentials::new("key", "secret", "170"));
+
+        assert_eq!(session.session_token().await.unwrap(), "first");
+        tokio::time::advance(DEFAULT_TOKEN_LIFETIME - Duration::from_secs(1)).await;
+        assert_eq!(session.session_token().await.unwrap(), "first");
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(session.session_token().await.unwrap(), "second");
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("GET /AuthenticateUser"));
+    }
+
+    #[tokio::test]
+    async fn test_answers_2fa_again_on_every_sign_in() {
+        let (api_url, requests) = canned_server(vec![
+            needs_2fa(),
+            signed_in("first"),
+            needs_2fa(),
+            signed_in("second"),
+        ])
+        .await;
+        let codes = Arc::new(AtomicU32::new(0));
+        let next_code = Arc::clone(&codes);
+        let session = AuthSession::new(&api_url, Credentials::new("key", "secret", "170"))
+            .with_two_factor(move || {
+                format!("{:06}", next_code.fetch_add(1, Ordering::Relaxed) + 1)
+            });
+
+        assert_eq!(session.session_token().await.unwrap(), "first");
+        session.invalidate().await;
+        assert_eq!(session.session_token().await.unwrap(), "second");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[1].starts_with("POST /Authenticate2FA"));
+        assert!(requests[1].contains("pending2fatoken: pending"));
+        assert!(requests[1].ends_with(r#"{"Code":"000001"}"#));
+        assert!(requests[3].ends_with(r#"{"Code":"000002"}"#));
+    }
+
+    #[tokio::test]
+    async fn test_2fa_without_code_provider_fails() {
+        let (api_url, _requests) = canned_server(vec![needs_2fa()]).await;
+        let session = AuthSession::new(&api_url, Credentials::new("key", "secret", "170"));
+        assert!(matches!(
+            session.session_token().await,
+            Err(NdaxError::Authentication(_))
+        ));
+    }
+
+    #[test]
+    fn test_signs_with_increasing_nonces() {
+        let credentials = Credentials::new("key", "secret", "170");
+        let first = credentials.auth_dict();
+        let second = credentials.clone().auth_dict();
+
+        let first_nonce: u64 = first["Nonce"].parse().unwrap();
+        let second_nonce: u64 = second["Nonce"].parse().unwrap();
+        assert!(second_nonce > first_nonce);
+        assert_eq!(first["UserId"], "170");
+        assert_eq!(first["APIKey"], "key");
+
+        let mut mac = HmacSha256::new_from_slice(b"secret").unwrap();
+        mac.update(format!("{}170key", first_nonce).as_bytes());
+        assert_eq!(first["Signature"], hex::encode(mac.finalize().into_bytes()));
+    }
+}
@@ -0,0 +1,171 @@
+use serde_json::json;
+use std::env;
+use tokio::sync::broadcast;
+use tokio::time::{self, Duration};
+use url::Url;
+
+use api_networking::constants;
+use api_networking::entities::trade_event::TradeEvent;
+use api_networking::error::NdaxError;
+use api_networking::exchange_manager::ExchangeManager;
+use api_networking::instrument_catalogue::InstrumentCatalogue;
+use api_networking::order_book_registry::OrderBookRegistry;
+use api_networking::recorder::MarketDataRecorder;
+use api_networking::trade_sink::{Compression, TradeSink};
+use api_networking::ws_client::{ConnectionState, WsClient};
+
+const DEFAULT_INSTRUMENTS: &str = "BTCCAD,USDCCAD";
+const ORDER_BOOK_DEPTH: usize = 10;
+const DEFAULT_MARKET_DATA_DIR: &str = "market_data";
+const TRADE_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[tokio::main]
+async fn main() -> Result<(), NdaxError> {
+    dotenv::dotenv().ok(); // Load .env file
+
+    let url = Url::parse(constants::WSS_URL)
+        .map_err(|e| NdaxError::Config(format!("Invalid WebSocket URL: {}", e)))?;
+    let api_url = Url::parse(constants::REST_URL)
+        .map_err(|e| NdaxError::Config(format!("Invalid REST URL: {}", e)))?;
+
+    let exchange_manager = ExchangeManager::new(api_url.as_ref());
+    let catalogue = InstrumentCatalogue::load(&exchange_manager).await?;
+
+    // Comma separated symbols to record, e.g. INSTRUMENTS=BTCCAD,USDCCAD
+    let symbols = env::var("INSTRUMENTS").unwrap_or_else(|_| DEFAULT_INSTRUMENTS.to_string());
+    let instrument_ids = symbols
+        .split(',')
+        .map(|symbol| catalogue.resolve(symbol.trim()))
+        .collect::<Result<Vec<u64>, NdaxError>>()?;
+
+    let order_books = OrderBookRegistry::new(ORDER_BOOK_DEPTH);
+    for &instrument_id in &instrument_ids {
+        order_books.add_instrument(instrument_id);
+    }
+
+    // match exchange_manager.get_assets().await {
+    //     Ok(assets) => println!("Asset codes: {:?}", assets),
+    //     Err(e) => println!("Error fetching asset codes: {:?}", e),
+    // }
+
+    // Connect to the WebSocket server
+    let client = WsClient::connect(&url).await?;
+    let mut events = client.events();
+
+    // let payload = json!({"OMSId":1,
+    // "InstrumentId":1,
+    // "Depth":100});
+
+    // match client.call(constants::GET_L2_SNAPSHOT, payload).await {
+    //     Ok(snapshot) => println!("L2 snapshot: {}", snapshot),
+    //     Err(e) => println!("Error fetching L2 snapshot: {}", e),
+    // }
+
+    for &instrument_id in &instrument_ids {
+        let trades_subscribe_payload = json!({"OMSId":1,
+            "InstrumentId":instrument_id,
+            "IncludeLastCount":10});
+
+        client
+            .subscribe(constants::SUBSCRIBE_TRADES, trades_subscribe_payload)
+            .await?;
+    }
+
+    for &instrument_id in &instrument_ids {
+        let order_book_subscribe_payload = json!({"OMSId":1,
+            "InstrumentId":instrument_id,
+            "Depth":ORDER_BOOK_DEPTH});
+
+        client
+            .subscribe(constants::SUBSCRIBE, order_book_subscribe_payload)
+            .await?;
+    }
+
+    let market_data_dir =
+        env::var("MARKET_DATA_DIR").unwrap_or_else(|_| DEFAULT_MARKET_DATA_DIR.to_string());
+    let mut recorder = MarketDataRecorder::new(&market_data_dir)?;
+    let mut trade_sink = TradeSink::new(&market_data_dir)?
+        .with_compression(Compression::Gzip)
+        .with_flush_interval(TRADE_FLUSH_INTERVAL);
+    // Quiet instruments may not trade again for hours, so buffered trades are
+    // flushed on a timer rather than only when the next one arrives
+    let mut flush_timer = time::interval(TRADE_FLUSH_INTERVAL);
+    // Books cannot be trusted while the feed is down; the Level2 snapshots
+    // sent when the session is restored bring them back in sync
+    let mut connection_state = client.connection_state();
+    let mut connection_events = client.connection_events();
+
+    loop {
+        let received = tokio::select! {
+            received = events.recv() => received,
+            _ = flush_timer.tick() => {
+                if let Err(e) = trade_sink.flush_if_due() {
+                    eprintln!("Error flushing trades: {}", e);
+                }
+                continue;
+            }
+            Ok(()) = connection_state.changed() => {
+                if *connection_state.borrow_and_update() == ConnectionState::Reconnecting {
+                    order_books.mark_all_stale();
+                }
+                continue;
+            }
+            Ok(event) = connection_events.recv() => {
+                eprintln!("WebSocket: {}", event);
+                continue;
+            }
+        };
+        let frame = match received {
+            Ok(frame) => frame,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                eprintln!("Skipped {} frames", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        println!("Update detected: {}", frame.function_name);
+        match frame.function_name.as_str() {
+            constants::SUBSCRIBE | constants::UPDATE => {
+                if let Err(e) = recorder.record_frame(&frame) {
+                    eprintln!("Error recording Level2 frame: {}", e);
+                }
+                for instrument_id in order_books.handle_frame(&frame) {
+                    if let Err(e) = order_books.resync(&client, instrument_id).await {
+                        eprintln!("Error resyncing order book {}: {}", instrument_id, e);
+                    }
+                }
+                for instrument_id in order_books.instruments() {
+                    if let Some(order_book) = order_books.snapshot(instrument_id) {
+                        println!("order book {}: {}", instrument_id, order_book);
+                    }
+                }
+            }
+            // Snapshots fetched to resync a book, already applied by the registry
+            constants::GET_L2_SNAPSHOT => {
+                if let Err(e) = recorder.record_frame(&frame) {
+                    eprintln!("Error recording Level2 frame: {}", e);
+                }
+            }
+            constants::SUBSCRIBE_LEVEL1 | constants::UPDATE_LEVEL1 => {
+                println!("level1: {}", frame);
+            }
+            constants::SUBSCRIBE_TRADES => {
+                println!("subscribe trades: {}", frame);
+            }
+            constants::UPDATE_TRADES => {
+                println!("Trade detected:{}", frame);
+                match frame.payload_as::<Vec<TradeEvent>>() {
+                    Ok(trades) => {
+                        if let Err(e) = trade_sink.write_all(&trades) {
+                            eprintln!("Error recording trades: {}", e);
+                        }
+                    }
+                    Err(e) => println!("Failed to parse trades: {}", e),
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
@@ -5,15 +5,51 @@ pub const REST_URL: &str = "https://api.ndax.io:8443/AP/";
 // Exchange Data Endpoints
 pub const PING: &str = "Ping";
 pub const ASSETS: &str = "Assets";
+pub const GET_INSTRUMENTS: &str = "GetInstruments";
+pub const GET_INSTRUMENT: &str = "GetInstrument";
+pub const GET_PRODUCTS: &str = "GetProducts";
+pub const GET_LEVEL1: &str = "GetLevel1";
+pub const GET_TICKER_HISTORY: &str = "GetTickerHistory";
+pub const GET_LAST_TRADES: &str = "GetLastTrades";
 
 // Order book messages
 pub const SUBSCRIBE: &str = "SubscribeLevel2";
 pub const UPDATE: &str = "Level2UpdateEvent";
 pub const SUBSCRIBE_TRADES: &str = "SubscribeTrades";
 pub const UPDATE_TRADES: &str = "TradeDataUpdateEvent";
+pub const GET_L2_SNAPSHOT: &str = "GetL2Snapshot";
+
+// Ticker messages
+pub const SUBSCRIBE_LEVEL1: &str = "SubscribeLevel1";
+pub const UNSUBSCRIBE_LEVEL1: &str = "UnsubscribeLevel1";
+pub const UPDATE_LEVEL1: &str = "Level1UpdateEvent";
+
+// Account events
+pub const SUBSCRIBE_ACCOUNT_EVENTS: &str = "SubscribeAccountEvents";
+pub const ACCOUNT_POSITION_EVENT: &str = "AccountPositionEvent";
+pub const DEPOSIT_TICKET_UPDATE_EVENT: &str = "DepositTicketUpdateEvent";
+pub const WITHDRAW_TICKET_UPDATE_EVENT: &str = "WithdrawTicketUpdateEvent";
+pub const ORDER_STATE_EVENT: &str = "OrderStateEvent";
+pub const ORDER_TRADE_EVENT: &str = "OrderTradeEvent";
+pub const CANCEL_ORDER_REJECT_EVENT: &str = "CancelOrderRejectEvent";
+pub const NEW_ORDER_REJECT_EVENT: &str = "NewOrderRejectEvent";
+pub const CANCEL_REPLACE_ORDER_REJECT_EVENT: &str = "CancelReplaceOrderRejectEvent";
+pub const CANCEL_ALL_ORDERS_REJECT_EVENT: &str = "CancelAllOrdersRejectEvent";
+pub const TRANSACTION_EVENT: &str = "TransactionEvent";
 
 // REST API Private Endpoints
 pub const GET_OPEN_ORDERS_PATH: &str = "GetOpenOrders";
 pub const USER_ACCOUNT_INFOS_PATH_URL: &str = "GetUserAccountInfos";
+pub const GET_ACCOUNT_POSITIONS_PATH_URL: &str = "GetAccountPositions";
+pub const GET_ACCOUNT_TRADES_PATH_URL: &str = "GetAccountTrades";
+pub const GET_ORDERS_HISTORY_PATH_URL: &str = "GetOrdersHistory";
+pub const GET_ORDER_STATUS_PATH_URL: &str = "GetOrderStatus";
+pub const GET_TRADES_HISTORY_PATH_URL: &str = "GetTradesHistory";
+pub const GET_ACCOUNT_TRANSACTIONS_PATH_URL: &str = "GetAccountTransactions";
 pub const AUTHENTICATE_USER_PATH_URL: &str = "AuthenticateUser";
+pub const AUTHENTICATE_2FA_PATH_URL: &str = "Authenticate2FA";
 pub const CANCEL_ALL_ORDERS_PATH_URL: &str = "CancelAllOrders";
+pub const SEND_ORDER_PATH_URL: &str = "SendOrder";
+pub const CANCEL_ORDER_PATH_URL: &str = "CancelOrder";
+pub const CANCEL_REPLACE_ORDER_PATH_URL: &str = "CancelReplaceOrder";
+pub const MODIFY_ORDER_PATH_URL: &str = "ModifyOrder";
@@ -0,0 +1,109 @@
+use csv::WriterBuilder;
+use futures_util::{pin_mut, Stream, TryStreamExt};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::NdaxError;
+
+// Appends one record to a CSV file without a header row, creating the file if needed
+pub fn append_to_csv<P: AsRef<Path>, T: Serialize>(path: P, record: &T) -> Result<(), NdaxError> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(file);
+    wtr.serialize(record)?;
+    wtr.flush()?;
+    Ok(())
+}
+
+// Writes records as CSV with a header row taken from the first record's field names
+pub fn write_csv<W, T, I>(writer: W, records: I) -> Result<usize, NdaxError>
+where
+    W: Write,
+    T: Serialize,
+    I: IntoIterator<Item = T>,
+{
+    let mut wtr = WriterBuilder::new().has_headers(true).from_writer(writer);
+    let mut count = 0;
+    for record in records {
+        wtr.serialize(record)?;
+        count += 1;
+    }
+    wtr.flush()?;
+    Ok(count)
+}
+
+/// Drains a history stream, e.g. `OrderManager::get_account_trades`, into a new
+/// CSV file with a header row.
+///
+/// Returns how many records were written. Rows already written stay in the
+/// file if a later page fails.
+pub async fn export_csv<P, T, S>(path: P, records: S) -> Result<usize, NdaxError>
+where
+    P: AsRef<Path>,
+    T: Serialize,
+    S: Stream<Item = Result<T, NdaxError>>,
+{
+    let mut wtr = WriterBuilder::new()
+        .has_headers(true)
+        .from_writer(File::create(path)?);
+    pin_mut!(records);
+    let mut count = 0;
+    while let Some(record) = records.try_next().await? {
+        wtr.serialize(&record)?;
+        count += 1;
+    }
+    wtr.flush()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::account_transaction::AccountTransaction;
+    use crate::test_support::temp_path;
+    use futures_util::stream;
+
+    fn transaction(transaction_id: u64, credit: &str) -> AccountTransaction {
+        serde_json::from_value(serde_json::json!({
+            "TransactionId": transaction_id, "AccountId": 185, "CR": credit, "DR": "0",
+            "TransactionType": "Trade", "ProductId": 1, "Balance": credit,
+            "TimeStamp": 1718007168597u64
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_write_csv_with_header() {
+        let mut output = Vec::new();
+        let count = write_csv(&mut output, [transaction(1, "0.1"), transaction(2, "0.2")]).unwrap();
+        assert_eq!(count, 2);
+
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("TransactionId,ReferenceId,OMSId,AccountId,CR,DR,"));
+        assert!(lines.next().unwrap().starts_with("1,0,0,185,0.1,0,"));
+        assert_eq!(lines.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_stops_at_error() {
+        let path = temp_path("export.csv");
+        let records = stream::iter(vec![
+            Ok(transaction(1, "0.1")),
+            Err(NdaxError::ConnectionClosed),
+            Ok(transaction(3, "0.3")),
+        ]);
+        assert!(matches!(
+            export_csv(&path, records).await,
+            Err(NdaxError::ConnectionClosed)
+        ));
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written.lines().count(), 2);
+    }
+}
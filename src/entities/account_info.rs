@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// One of the accounts returned by GetUserAccountInfos.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountInfo {
+    #[serde(rename = "OMSID")]
+    pub oms_id: u64,
+    #[serde(rename = "AccountId")]
+    pub account_id: u64,
+    #[serde(rename = "AccountName")]
+    pub account_name: String,
+    #[serde(rename = "AccountHandle", default)]
+    pub account_handle: Option<String>,
+    #[serde(rename = "AccountType", default)]
+    pub account_type: Option<String>,
+    #[serde(rename = "FeeGroupId", default)]
+    pub fee_group_id: u64,
+    #[serde(rename = "VerificationLevel", default)]
+    pub verification_level: u64,
+    #[serde(rename = "Frozen", default)]
+    pub frozen: bool,
+}
@@ -0,0 +1,53 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// One ledger entry from GetAccountTransactions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountTransaction {
+    #[serde(rename = "TransactionId")]
+    pub transaction_id: u64,
+    #[serde(rename = "ReferenceId", default)]
+    pub reference_id: u64,
+    #[serde(rename = "OMSId", default)]
+    pub oms_id: u64,
+    #[serde(rename = "AccountId")]
+    pub account_id: u64,
+    // Credit
+    #[serde(rename = "CR")]
+    pub credit: Decimal,
+    // Debit
+    #[serde(rename = "DR")]
+    pub debit: Decimal,
+    #[serde(rename = "Counterparty", default)]
+    pub counterparty: u64,
+    // e.g. Fee, Trade, Deposit, Withdraw
+    #[serde(rename = "TransactionType")]
+    pub transaction_type: String,
+    #[serde(rename = "ReferenceType", default)]
+    pub reference_type: String,
+    #[serde(rename = "ProductId")]
+    pub product_id: u64,
+    #[serde(rename = "Balance")]
+    pub balance: Decimal,
+    #[serde(rename = "TimeStamp")]
+    pub time_stamp: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_account_transaction() {
+        let transaction: AccountTransaction = serde_json::from_str(
+            r#"{"TransactionId":24214,"ReferenceId":213,"OMSId":1,"AccountId":185,
+                "CR":0.1,"DR":0,"Counterparty":3,"TransactionType":"Trade",
+                "ReferenceType":"Trade","ProductId":1,"Balance":1.6,
+                "TimeStamp":1718007168597}"#,
+        )
+        .unwrap();
+        assert_eq!(transaction.transaction_type, "Trade");
+        assert_eq!(transaction.credit, Decimal::new(1, 1));
+        assert_eq!(transaction.balance, Decimal::new(16, 1));
+    }
+}
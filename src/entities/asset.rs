@@ -0,0 +1,35 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// An asset as listed by the public Assets endpoint, which is keyed by asset symbol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub unified_cryptoasset_id: u64,
+    #[serde(default)]
+    pub can_withdraw: Option<bool>,
+    #[serde(default)]
+    pub can_deposit: Option<bool>,
+    #[serde(default)]
+    pub min_withdraw: Option<Decimal>,
+    #[serde(default)]
+    pub max_withdraw: Option<Decimal>,
+    #[serde(default)]
+    pub maker_fee: Option<Decimal>,
+    #[serde(default)]
+    pub taker_fee: Option<Decimal>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_asset_codes() {
+        let assets: Vec<Asset> =
+            serde_json::from_str(include_str!("../utils/asset_codes")).unwrap();
+        let bitcoin = assets.iter().find(|a| a.name == "Bitcoin").unwrap();
+        assert_eq!(bitcoin.unified_cryptoasset_id, 31652);
+        assert_eq!(bitcoin.min_withdraw, Some(Decimal::new(1, 8)));
+    }
+}
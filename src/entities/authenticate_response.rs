@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthenticatedUser {
+    #[serde(rename = "UserId")]
+    pub user_id: u64,
+    #[serde(rename = "UserName")]
+    pub user_name: String,
+    #[serde(rename = "Email", default)]
+    pub email: Option<String>,
+    #[serde(rename = "AccountId")]
+    pub account_id: u64,
+    #[serde(rename = "OMSId")]
+    pub oms_id: u64,
+    #[serde(rename = "Use2FA", default)]
+    pub use_2fa: bool,
+}
+
+/// The reply to AuthenticateUser, over REST or the WebSocket gateway.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthenticateResponse {
+    #[serde(rename = "Authenticated")]
+    pub authenticated: bool,
+    #[serde(rename = "SessionToken", default)]
+    pub session_token: Option<String>,
+    #[serde(rename = "User", default)]
+    pub user: Option<AuthenticatedUser>,
+    #[serde(rename = "Locked", default)]
+    pub locked: bool,
+    #[serde(rename = "Requires2FA", default)]
+    pub requires_2fa: bool,
+    #[serde(rename = "TwoFAType", default)]
+    pub two_fa_type: Option<String>,
+    #[serde(rename = "TwoFAToken", default)]
+    pub two_fa_token: Option<String>,
+    #[serde(default)]
+    pub errormsg: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_authenticated() {
+        let response: AuthenticateResponse = serde_json::from_str(
+            r#"{"Authenticated":true,"SessionToken":"7d0ccf3a-ae63-44f5-a409-2301d80228bc",
+                "User":{"UserId":170,"UserName":"trader","Email":"trader@example.com",
+                "EmailVerified":true,"AccountId":185,"OMSId":1,"Use2FA":false},
+                "Locked":false,"Requires2FA":false,"EnforceEnable2FA":false,
+                "TwoFAType":null,"TwoFAToken":null,"errormsg":null}"#,
+        )
+        .unwrap();
+        assert!(response.authenticated);
+        assert_eq!(
+            response.session_token.as_deref(),
+            Some("7d0ccf3a-ae63-44f5-a409-2301d80228bc")
+        );
+        assert_eq!(response.user.unwrap().account_id, 185);
+    }
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::order_types::OrderId;
+
+/// Sent when NDAX refuses to cancel one of the account's orders.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelOrderRejectEvent {
+    #[serde(rename = "OMSId", default)]
+    pub oms_id: u64,
+    #[serde(rename = "AccountId")]
+    pub account_id: u64,
+    #[serde(rename = "OrderId")]
+    pub order_id: OrderId,
+    #[serde(rename = "OrderRevision", default)]
+    pub order_revision: u64,
+    #[serde(rename = "OrderType", default)]
+    pub order_type: String,
+    #[serde(rename = "InstrumentId")]
+    pub instrument_id: u64,
+    #[serde(rename = "Status", default)]
+    pub status: String,
+    #[serde(rename = "RejectReason", default)]
+    pub reject_reason: String,
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::order_types::{ClientOrderId, OrderId};
+
+/// The reply to CancelReplaceOrder, linking the replaced order to its replacement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelReplaceResponse {
+    #[serde(rename = "ReplacementOrderId")]
+    pub replacement_order_id: OrderId,
+    #[serde(rename = "ReplacementClOrdId", default = "no_client_order_id")]
+    pub replacement_client_order_id: ClientOrderId,
+    #[serde(rename = "OrigOrderId")]
+    pub orig_order_id: OrderId,
+    #[serde(rename = "OrigClOrdId", default = "no_client_order_id")]
+    pub orig_client_order_id: ClientOrderId,
+}
+
+fn no_client_order_id() -> ClientOrderId {
+    ClientOrderId(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_cancel_replace_response() {
+        let response: CancelReplaceResponse = serde_json::from_str(
+            r#"{"ReplacementOrderId":6714,"ReplacementClOrdId":43,"OrigOrderId":6713,"OrigClOrdId":42}"#,
+        )
+        .unwrap();
+        assert_eq!(response.replacement_order_id, OrderId(6714));
+        assert_eq!(response.replacement_client_order_id, ClientOrderId(43));
+        assert_eq!(response.orig_order_id, OrderId(6713));
+    }
+}
@@ -0,0 +1,48 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// One OHLC bar from GetTickerHistory.
+///
+/// The gateway sends bars as arrays; the fields below are declared in array
+/// order so the struct deserializes directly from that form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    #[serde(rename = "EndDateTime")]
+    pub end_time: u64,
+    #[serde(rename = "High")]
+    pub high: Decimal,
+    #[serde(rename = "Low")]
+    pub low: Decimal,
+    #[serde(rename = "Open")]
+    pub open: Decimal,
+    #[serde(rename = "Close")]
+    pub close: Decimal,
+    #[serde(rename = "Volume")]
+    pub volume: Decimal,
+    #[serde(rename = "InsideBidPrice")]
+    pub inside_bid_price: Decimal,
+    #[serde(rename = "InsideAskPrice")]
+    pub inside_ask_price: Decimal,
+    #[serde(rename = "InstrumentId")]
+    pub instrument_id: u64,
+    #[serde(rename = "BeginDateTime")]
+    pub begin_time: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_from_array() {
+        let candle: Candle = serde_json::from_str(
+            "[1718007240000,5715.2,5708.1,5711.0,5711.7,0.53,5711.7,5711.8,1,1718007180000]",
+        )
+        .unwrap();
+        assert_eq!(candle.begin_time, 1718007180000);
+        assert_eq!(candle.open, Decimal::new(57110, 1));
+        assert_eq!(candle.close, Decimal::new(57117, 1));
+        assert_eq!(candle.volume, Decimal::new(53, 2));
+        assert_eq!(candle.instrument_id, 1);
+    }
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::NdaxError;
+
+/// The standard reply to NDAX calls that only report success or failure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenericResponse {
+    pub result: bool,
+    #[serde(default)]
+    pub errormsg: Option<String>,
+    #[serde(default)]
+    pub errorcode: i64,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+impl GenericResponse {
+    // Turns a reply reporting failure into an error
+    pub fn into_result(self) -> Result<Self, NdaxError> {
+        if self.result {
+            Ok(self)
+        } else {
+            Err(self.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_error() {
+        let response: GenericResponse = serde_json::from_str(
+            r#"{"result":false,"errormsg":"Operation Failed","errorcode":101,"detail":null}"#,
+        )
+        .unwrap();
+        assert!(!response.result);
+        assert_eq!(response.errormsg.as_deref(), Some("Operation Failed"));
+        assert_eq!(response.errorcode, 101);
+        assert_eq!(response.detail, None);
+    }
+}
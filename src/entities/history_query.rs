@@ -0,0 +1,45 @@
+/// Which part of an account's history to fetch and how many rows per request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryQuery {
+    pub instrument_id: Option<u64>,
+    // POSIX seconds, inclusive
+    pub start_time: Option<u64>,
+    // POSIX seconds, exclusive
+    pub end_time: Option<u64>,
+    pub page_size: usize,
+}
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+impl Default for HistoryQuery {
+    fn default() -> Self {
+        HistoryQuery {
+            instrument_id: None,
+            start_time: None,
+            end_time: None,
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl HistoryQuery {
+    pub fn new() -> Self {
+        HistoryQuery::default()
+    }
+
+    pub fn with_instrument(mut self, instrument_id: u64) -> Self {
+        self.instrument_id = Some(instrument_id);
+        self
+    }
+
+    pub fn with_time_range(mut self, start_time: u64, end_time: u64) -> Self {
+        self.start_time = Some(start_time);
+        self.end_time = Some(end_time);
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+}
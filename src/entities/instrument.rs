@@ -0,0 +1,71 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::increments::Increments;
+
+/// A tradable pair, as returned by GetInstruments and GetInstrument.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instrument {
+    #[serde(rename = "InstrumentId")]
+    pub id: u64,
+    #[serde(rename = "Symbol")]
+    pub symbol: String,
+    // The product bought and sold, e.g. BTC in BTCCAD
+    #[serde(rename = "Product1")]
+    pub product1: u64,
+    #[serde(rename = "Product1Symbol", default)]
+    pub product1_symbol: String,
+    // The product prices are quoted in, e.g. CAD in BTCCAD
+    #[serde(rename = "Product2")]
+    pub product2: u64,
+    #[serde(rename = "Product2Symbol", default)]
+    pub product2_symbol: String,
+    #[serde(rename = "PriceIncrement")]
+    pub price_increment: Decimal,
+    #[serde(rename = "QuantityIncrement")]
+    pub quantity_increment: Decimal,
+    #[serde(rename = "MinimumQuantity", default)]
+    pub min_quantity: Decimal,
+    // e.g. Running, Paused, Stopped
+    #[serde(rename = "SessionStatus", default)]
+    pub session_status: String,
+}
+
+impl Instrument {
+    pub fn increments(&self) -> Increments {
+        Increments::new(
+            self.price_increment,
+            self.quantity_increment,
+            self.min_quantity,
+        )
+    }
+
+    pub fn is_trading(&self) -> bool {
+        self.session_status == "Running"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_instrument() {
+        let instrument: Instrument = serde_json::from_str(
+            r#"{"OMSId":1,"InstrumentId":1,"Symbol":"BTCCAD","Product1":1,
+                "Product1Symbol":"BTC","Product2":3,"Product2Symbol":"CAD",
+                "InstrumentType":"Standard","VenueInstrumentId":1,"VenueId":1,
+                "SortIndex":0,"SessionStatus":"Running","PreviousSessionStatus":"Paused",
+                "SessionStatusDateTime":"2024-06-10T08:19:28Z","SelfTradePrevention":true,
+                "QuantityIncrement":0.00000001,"PriceIncrement":0.1,
+                "MinimumQuantity":0.0001,"MinimumPrice":0.1}"#,
+        )
+        .unwrap();
+        assert_eq!(instrument.symbol, "BTCCAD");
+        assert!(instrument.is_trading());
+        assert_eq!(
+            instrument.increments(),
+            Increments::new(Decimal::new(1, 1), Decimal::new(1, 8), Decimal::new(1, 4))
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// Top of book and session statistics for one instrument, from GetLevel1,
+/// SubscribeLevel1 and Level1UpdateEvent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level1 {
+    #[serde(rename = "OMSId", default)]
+    pub oms_id: u64,
+    #[serde(rename = "InstrumentId")]
+    pub instrument_id: u64,
+    #[serde(rename = "BestBid")]
+    pub best_bid: Decimal,
+    #[serde(rename = "BestOffer")]
+    pub best_offer: Decimal,
+    #[serde(rename = "BidQty", default)]
+    pub bid_qty: Decimal,
+    #[serde(rename = "AskQty", default)]
+    pub ask_qty: Decimal,
+    #[serde(rename = "LastTradedPx")]
+    pub last_traded_px: Decimal,
+    #[serde(rename = "LastTradedQty", default)]
+    pub last_traded_qty: Decimal,
+    #[serde(rename = "LastTradeTime", default)]
+    pub last_trade_time: u64,
+    #[serde(rename = "SessionOpen", default)]
+    pub session_open: Decimal,
+    #[serde(rename = "SessionHigh", default)]
+    pub session_high: Decimal,
+    #[serde(rename = "SessionLow", default)]
+    pub session_low: Decimal,
+    #[serde(rename = "SessionClose", default)]
+    pub session_close: Decimal,
+    #[serde(rename = "Volume", default)]
+    pub volume: Decimal,
+    #[serde(rename = "CurrentDayVolume", default)]
+    pub current_day_volume: Decimal,
+    #[serde(rename = "CurrentDayNumTrades", default)]
+    pub current_day_num_trades: u64,
+    #[serde(rename = "CurrentDayPxChange", default)]
+    pub current_day_px_change: Decimal,
+    #[serde(rename = "Rolling24HrVolume", default)]
+    pub rolling_24hr_volume: Decimal,
+    #[serde(rename = "Rolling24HrPxChange", default)]
+    pub rolling_24hr_px_change: Decimal,
+    // Sent as a number or as a numeric string depending on the endpoint
+    #[serde(rename = "TimeStamp", default, deserialize_with = "number_or_string")]
+    pub time_stamp: u64,
+}
+
+fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(u64),
+        String(String),
+    }
+
+    match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(value) => Ok(value),
+        NumberOrString::String(value) => value.parse().map_err(de::Error::custom),
+    }
+}
+
+impl Level1 {
+    pub fn spread(&self) -> Decimal {
+        self.best_offer - self.best_bid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_level1() {
+        let level1: Level1 = serde_json::from_str(
+            r#"{"OMSId":1,"InstrumentId":1,"BestBid":5711.7,"BestOffer":5711.8,
+                "LastTradedPx":5711.7,"LastTradedQty":0.0075,"LastTradeTime":1718007168597,
+                "SessionOpen":5650.1,"SessionHigh":5750.0,"SessionLow":5600.2,
+                "SessionClose":5711.7,"Volume":0.0075,"CurrentDayVolume":12.5,
+                "CurrentDayNumTrades":310,"CurrentDayPxChange":61.6,"Rolling24HrVolume":14.2,
+                "Rolling24NumTrades":355,"Rolling24HrPxChange":1.09,"TimeStamp":"1718007168600",
+                "BidQty":0.0075,"AskQty":8.13439401,"BidOrderCt":0,"AskOrderCt":0,
+                "Rolling24HrPxChangePercent":1.09}"#,
+        )
+        .unwrap();
+        assert_eq!(level1.best_bid, Decimal::new(57117, 1));
+        assert_eq!(level1.spread(), Decimal::new(1, 1));
+        assert_eq!(level1.current_day_num_trades, 310);
+        assert_eq!(level1.time_stamp, 1718007168600);
+
+        let level1: Level1 = serde_json::from_str(
+            r#"{"InstrumentId":1,"BestBid":5711.7,"BestOffer":5711.8,"LastTradedPx":5711.7,
+                "TimeStamp":1718007168600}"#,
+        )
+        .unwrap();
+        assert_eq!(level1.time_stamp, 1718007168600);
+    }
+}
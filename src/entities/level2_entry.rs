@@ -0,0 +1,159 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::entities::decimal_number;
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum ActionType {
+    New,
+    Update,
+    Delete,
+}
+
+impl TryFrom<u8> for ActionType {
+    type Error = String;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        match code {
+            0 => Ok(ActionType::New),
+            1 => Ok(ActionType::Update),
+            2 => Ok(ActionType::Delete),
+            _ => Err(format!("unknown Level2 action type: {}", code)),
+        }
+    }
+}
+
+impl From<ActionType> for u8 {
+    fn from(action_type: ActionType) -> Self {
+        match action_type {
+            ActionType::New => 0,
+            ActionType::Update => 1,
+            ActionType::Delete => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl TryFrom<u8> for Side {
+    type Error = String;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        match code {
+            0 => Ok(Side::Buy),
+            1 => Ok(Side::Sell),
+            _ => Err(format!("unknown side: {}", code)),
+        }
+    }
+}
+
+impl From<Side> for u8 {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        }
+    }
+}
+
+/// One row of a SubscribeLevel2 / GetL2Snapshot reply or a Level2UpdateEvent.
+///
+/// The gateway sends rows as arrays; the fields below are declared in array order
+/// so the struct deserializes directly from that form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level2Entry {
+    #[serde(rename = "MDUpdateId")]
+    pub md_update_id: u64,
+    #[serde(rename = "Accounts")]
+    pub accounts: u64,
+    #[serde(rename = "ActionDateTime")]
+    pub action_date_time: u64,
+    #[serde(rename = "ActionType")]
+    pub action_type: ActionType,
+    #[serde(rename = "LastTradePrice")]
+    pub last_trade_price: Decimal,
+    #[serde(rename = "Orders")]
+    pub orders: u64,
+    #[serde(rename = "Price")]
+    pub price: Decimal,
+    #[serde(rename = "ProductPairCode")]
+    pub product_pair_code: u64,
+    #[serde(rename = "Quantity")]
+    pub quantity: Decimal,
+    #[serde(rename = "Side")]
+    pub side: Side,
+}
+
+impl Level2Entry {
+    // The array form the gateway sends, e.g. to rebuild a Level2UpdateEvent
+    pub fn to_row(&self) -> Value {
+        json!([
+            self.md_update_id,
+            self.accounts,
+            self.action_date_time,
+            u8::from(self.action_type),
+            decimal_number(self.last_trade_price),
+            self.orders,
+            decimal_number(self.price),
+            self.product_pair_code,
+            decimal_number(self.quantity),
+            u8::from(self.side)
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_from_array() {
+        let entry: Level2Entry =
+            serde_json::from_str("[262,1,1718007168597,2,5711.70000,0,5708.20000,1,0.00749800,0]")
+                .unwrap();
+        assert_eq!(entry.md_update_id, 262);
+        assert_eq!(entry.action_date_time, 1718007168597);
+        assert_eq!(entry.action_type, ActionType::Delete);
+        assert_eq!(entry.last_trade_price, Decimal::new(57117, 1));
+        assert_eq!(entry.price, Decimal::new(57082, 1));
+        assert_eq!(entry.product_pair_code, 1);
+        assert_eq!(entry.quantity, Decimal::new(749800, 8));
+        assert_eq!(entry.side, Side::Buy);
+
+        let row = entry.to_row();
+        assert_eq!(serde_json::from_value::<Level2Entry>(row).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_keeps_every_digit() {
+        // 16 significant digits, more than an f64 holds
+        let entry: Level2Entry = serde_json::from_str(
+            "[262,1,1718007168597,0,99999999.99999999,1,12345678.12345678,1,0.00749800,0]",
+        )
+        .unwrap();
+        assert_eq!(entry.last_trade_price, Decimal::new(9999999999999999, 8));
+        assert_eq!(entry.price, Decimal::new(1234567812345678, 8));
+
+        let row = entry.to_row();
+        assert_eq!(
+            row.to_string(),
+            "[262,1,1718007168597,0,99999999.99999999,1,12345678.12345678,1,0.00749800,0]"
+        );
+        assert_eq!(serde_json::from_value::<Level2Entry>(row).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_deserialize_rejects_unknown_side() {
+        let result =
+            serde_json::from_str::<Level2Entry>("[1,1,1718003785385,0,0,1,5711.8,1,8.1,7]");
+        assert!(result.is_err());
+    }
+}
@@ -1 +1,34 @@
+use rust_decimal::Decimal;
+use serde_json::{Number, Value};
+use std::str::FromStr;
+
+pub mod account_info;
+pub mod account_transaction;
+pub mod asset;
+pub mod authenticate_response;
+pub mod cancel_order_reject_event;
+pub mod cancel_replace_response;
+pub mod candle;
+pub mod generic_response;
+pub mod history_query;
+pub mod instrument;
+pub mod level1;
+pub mod level2_entry;
+pub mod new_order;
+pub mod open_order;
+pub mod order_trade_event;
+pub mod order_types;
+pub mod pong;
+pub mod position;
+pub mod product;
+pub mod send_order_response;
+pub mod ticket_update;
 pub mod trade_event;
+
+// A decimal as an exact JSON number, e.g. for the gateway's array rows; going
+// through f64 would lose digits past its 15-16 significant ones
+pub(crate) fn decimal_number(value: Decimal) -> Value {
+    Number::from_str(&value.to_string())
+        .expect("a decimal is always a valid JSON number")
+        .into()
+}
@@ -0,0 +1,406 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::entities::level2_entry::Side;
+use crate::entities::order_types::{ClientOrderId, OrderId, OrderType, TimeInForce};
+use crate::error::NdaxError;
+use crate::increments::Increments;
+
+// PegPriceType trailing stops follow: the last traded price
+const PEG_TO_LAST: u8 = 1;
+
+/// An order to be placed with SendOrder.
+///
+/// Start from one of the constructors for the order type, then adjust with the
+/// `with_*` methods, e.g. `NewOrder::limit(1, Side::Buy, qty, px).with_post_only()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewOrder {
+    pub instrument_id: u64,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub quantity: Decimal,
+    pub limit_price: Option<Decimal>,
+    pub stop_price: Option<Decimal>,
+    pub trailing_amount: Option<Decimal>,
+    pub limit_offset: Option<Decimal>,
+    // Visible part of a reserve (iceberg) order
+    pub display_quantity: Option<Decimal>,
+    pub time_in_force: TimeInForce,
+    pub post_only: bool,
+    pub client_order_id: Option<ClientOrderId>,
+}
+
+impl NewOrder {
+    fn new(instrument_id: u64, side: Side, order_type: OrderType, quantity: Decimal) -> Self {
+        NewOrder {
+            instrument_id,
+            side,
+            order_type,
+            quantity,
+            limit_price: None,
+            stop_price: None,
+            trailing_amount: None,
+            limit_offset: None,
+            display_quantity: None,
+            time_in_force: TimeInForce::default(),
+            post_only: false,
+            client_order_id: None,
+        }
+    }
+
+    pub fn market(instrument_id: u64, side: Side, quantity: Decimal) -> Self {
+        NewOrder::new(instrument_id, side, OrderType::Market, quantity)
+    }
+
+    pub fn limit(instrument_id: u64, side: Side, quantity: Decimal, limit_price: Decimal) -> Self {
+        NewOrder {
+            limit_price: Some(limit_price),
+            ..NewOrder::new(instrument_id, side, OrderType::Limit, quantity)
+        }
+    }
+
+    pub fn stop_market(
+        instrument_id: u64,
+        side: Side,
+        quantity: Decimal,
+        stop_price: Decimal,
+    ) -> Self {
+        NewOrder {
+            stop_price: Some(stop_price),
+            ..NewOrder::new(instrument_id, side, OrderType::StopMarket, quantity)
+        }
+    }
+
+    pub fn stop_limit(
+        instrument_id: u64,
+        side: Side,
+        quantity: Decimal,
+        stop_price: Decimal,
+        limit_price: Decimal,
+    ) -> Self {
+        NewOrder {
+            stop_price: Some(stop_price),
+            limit_price: Some(limit_price),
+            ..NewOrder::new(instrument_id, side, OrderType::StopLimit, quantity)
+        }
+    }
+
+    // Stop that follows the last price at a fixed distance, then goes to market
+    pub fn trailing_stop_market(
+        instrument_id: u64,
+        side: Side,
+        quantity: Decimal,
+        trailing_amount: Decimal,
+    ) -> Self {
+        NewOrder {
+            trailing_amount: Some(trailing_amount),
+            ..NewOrder::new(instrument_id, side, OrderType::TrailingStopMarket, quantity)
+        }
+    }
+
+    // Trailing stop that places a limit order limit_offset away from the stop once triggered
+    pub fn trailing_stop_limit(
+        instrument_id: u64,
+        side: Side,
+        quantity: Decimal,
+        trailing_amount: Decimal,
+        limit_offset: Decimal,
+    ) -> Self {
+        NewOrder {
+            trailing_amount: Some(trailing_amount),
+            limit_offset: Some(limit_offset),
+            ..NewOrder::new(instrument_id, side, OrderType::TrailingStopLimit, quantity)
+        }
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    // Only rest on the book, never take liquidity
+    pub fn with_post_only(mut self) -> Self {
+        self.post_only = true;
+        self
+    }
+
+    pub fn with_client_order_id(mut self, client_order_id: ClientOrderId) -> Self {
+        self.client_order_id = Some(client_order_id);
+        self
+    }
+
+    // Turns the order into a reserve order showing only display_quantity at a time
+    pub fn with_display_quantity(mut self, display_quantity: Decimal) -> Self {
+        self.display_quantity = Some(display_quantity);
+        self
+    }
+
+    /// Checks the order is well formed and sits on the instrument's ticks and lots.
+    pub fn validate(&self, increments: &Increments) -> Result<(), NdaxError> {
+        if !increments.is_valid_quantity(self.quantity) {
+            return Err(invalid(format!(
+                "quantity {} is not a multiple of {} of at least {}",
+                self.quantity, increments.quantity_increment, increments.min_quantity
+            )));
+        }
+
+        let limit_price = required(
+            self.limit_price,
+            self.order_type.has_limit_price(),
+            "limit price",
+        )?;
+        if let Some(price) = limit_price {
+            check_price(increments, "limit price", price)?;
+        }
+        let stop_price = required(
+            self.stop_price,
+            self.order_type.has_stop_price(),
+            "stop price",
+        )?;
+        if let Some(price) = stop_price {
+            check_price(increments, "stop price", price)?;
+        }
+        let trailing_amount = required(
+            self.trailing_amount,
+            self.order_type.is_trailing(),
+            "trailing amount",
+        )?;
+        if let Some(amount) = trailing_amount {
+            check_price(increments, "trailing amount", amount)?;
+        }
+        let limit_offset = required(
+            self.limit_offset,
+            self.order_type == OrderType::TrailingStopLimit,
+            "limit offset",
+        )?;
+        if let Some(offset) = limit_offset {
+            if offset < Decimal::ZERO || increments.round_price_down(offset) != offset {
+                return Err(invalid(format!(
+                    "limit offset {} is not a multiple of {}",
+                    offset, increments.price_increment
+                )));
+            }
+        }
+
+        if let Some(display_quantity) = self.display_quantity {
+            if !self.order_type.has_limit_price() {
+                return Err(invalid(
+                    "reserve orders must have a limit price".to_string(),
+                ));
+            }
+            if !increments.is_valid_quantity(display_quantity) || display_quantity > self.quantity {
+                return Err(invalid(format!(
+                    "display quantity {} must be a valid lot no larger than {}",
+                    display_quantity, self.quantity
+                )));
+            }
+        }
+
+        if self.post_only {
+            if self.order_type != OrderType::Limit {
+                return Err(invalid("post-only orders must be limit orders".to_string()));
+            }
+            if self.time_in_force != TimeInForce::Gtc {
+                return Err(invalid("post-only orders cannot be IOC or FOK".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    // Body of the SendOrder request for the given account
+    pub fn to_payload(&self, oms_id: u64, account_id: u64) -> serde_json::Value {
+        let request = SendOrderRequest {
+            oms_id,
+            account_id,
+            instrument_id: self.instrument_id,
+            side: self.side,
+            order_type: self.order_type,
+            quantity: self.quantity,
+            limit_price: self.limit_price.unwrap_or_default(),
+            stop_price: self.stop_price.unwrap_or_default(),
+            trailing_amount: self.trailing_amount.unwrap_or_default(),
+            limit_offset: self.limit_offset.unwrap_or_default(),
+            peg_price_type: PEG_TO_LAST,
+            use_display_quantity: self.display_quantity.is_some(),
+            display_quantity: self.display_quantity.unwrap_or_default(),
+            time_in_force: self.time_in_force,
+            post_only: self.post_only,
+            client_order_id: self.client_order_id.unwrap_or(ClientOrderId(0)),
+        };
+        serde_json::to_value(request).expect("SendOrder request is always valid JSON")
+    }
+
+    // Body of a CancelReplaceOrder request swapping this order in for order_id
+    pub fn to_replace_payload(
+        &self,
+        oms_id: u64,
+        account_id: u64,
+        order_id: OrderId,
+    ) -> serde_json::Value {
+        let mut payload = self.to_payload(oms_id, account_id);
+        let fields = payload
+            .as_object_mut()
+            .expect("SendOrder request is a JSON object");
+        // CancelReplaceOrder spells the client id differently from SendOrder
+        if let Some(client_order_id) = fields.remove("ClientOrderId") {
+            fields.insert("ClientOrdId".to_string(), client_order_id);
+        }
+        fields.insert("OrderIdToReplace".to_string(), order_id.0.into());
+        payload
+    }
+}
+
+// Prices go on the wire as JSON numbers, which the gateway requires
+#[derive(Serialize)]
+struct SendOrderRequest {
+    #[serde(rename = "OMSId")]
+    oms_id: u64,
+    #[serde(rename = "AccountId")]
+    account_id: u64,
+    #[serde(rename = "InstrumentId")]
+    instrument_id: u64,
+    #[serde(rename = "Side")]
+    side: Side,
+    #[serde(rename = "OrderType")]
+    order_type: OrderType,
+    #[serde(rename = "Quantity", with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+    #[serde(rename = "LimitPrice", with = "rust_decimal::serde::float")]
+    limit_price: Decimal,
+    #[serde(rename = "StopPrice", with = "rust_decimal::serde::float")]
+    stop_price: Decimal,
+    #[serde(rename = "TrailingAmount", with = "rust_decimal::serde::float")]
+    trailing_amount: Decimal,
+    #[serde(rename = "LimitOffset", with = "rust_decimal::serde::float")]
+    limit_offset: Decimal,
+    #[serde(rename = "PegPriceType")]
+    peg_price_type: u8,
+    #[serde(rename = "UseDisplayQuantity")]
+    use_display_quantity: bool,
+    #[serde(rename = "DisplayQuantity", with = "rust_decimal::serde::float")]
+    display_quantity: Decimal,
+    #[serde(rename = "TimeInForce")]
+    time_in_force: TimeInForce,
+    #[serde(rename = "PostOnly")]
+    post_only: bool,
+    #[serde(rename = "ClientOrderId")]
+    client_order_id: ClientOrderId,
+}
+
+fn invalid(message: String) -> NdaxError {
+    NdaxError::InvalidOrder(message)
+}
+
+// Ensures a field is present exactly when the order type uses it
+fn required(
+    value: Option<Decimal>,
+    is_required: bool,
+    name: &str,
+) -> Result<Option<Decimal>, NdaxError> {
+    match (value, is_required) {
+        (None, true) => Err(invalid(format!("{} is required", name))),
+        (Some(_), false) => Err(invalid(format!("{} is not used by this order type", name))),
+        _ => Ok(value),
+    }
+}
+
+fn check_price(increments: &Increments, name: &str, price: Decimal) -> Result<(), NdaxError> {
+    if increments.is_valid_price(price) {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "{} {} is not a positive multiple of {}",
+            name, price, increments.price_increment
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn btc_cad() -> Increments {
+        Increments::new(dec("0.1"), dec("0.00000001"), dec("0.0001"))
+    }
+
+    #[test]
+    fn test_validates_against_increments() {
+        let increments = btc_cad();
+        assert!(NewOrder::limit(1, Side::Buy, dec("0.5"), dec("5711.8"))
+            .validate(&increments)
+            .is_ok());
+        assert!(NewOrder::market(1, Side::Sell, dec("0.00749800"))
+            .with_time_in_force(TimeInForce::Ioc)
+            .validate(&increments)
+            .is_ok());
+
+        let off_tick = NewOrder::limit(1, Side::Buy, dec("0.5"), dec("5711.85"));
+        assert!(matches!(
+            off_tick.validate(&increments),
+            Err(NdaxError::InvalidOrder(_))
+        ));
+        let below_minimum = NewOrder::market(1, Side::Buy, dec("0.00001"));
+        assert!(below_minimum.validate(&increments).is_err());
+        let bad_stop = NewOrder::stop_limit(1, Side::Sell, dec("0.5"), dec("5600.05"), dec("5600"));
+        assert!(bad_stop.validate(&increments).is_err());
+    }
+
+    #[test]
+    fn test_rejects_inconsistent_flags() {
+        let increments = btc_cad();
+        let post_only_market = NewOrder::market(1, Side::Buy, dec("0.5")).with_post_only();
+        assert!(post_only_market.validate(&increments).is_err());
+
+        let post_only_ioc = NewOrder::limit(1, Side::Buy, dec("0.5"), dec("5711.8"))
+            .with_post_only()
+            .with_time_in_force(TimeInForce::Ioc);
+        assert!(post_only_ioc.validate(&increments).is_err());
+
+        let oversized_reserve = NewOrder::limit(1, Side::Buy, dec("0.5"), dec("5711.8"))
+            .with_display_quantity(dec("1"));
+        assert!(oversized_reserve.validate(&increments).is_err());
+
+        let trailing =
+            NewOrder::trailing_stop_limit(1, Side::Sell, dec("0.5"), dec("25"), dec("0"));
+        assert!(trailing.validate(&increments).is_ok());
+    }
+
+    #[test]
+    fn test_payload() {
+        let order = NewOrder::limit(1, Side::Sell, dec("0.25"), dec("5711.8"))
+            .with_display_quantity(dec("0.05"))
+            .with_client_order_id(ClientOrderId(42));
+        assert_eq!(
+            order.to_payload(1, 185),
+            json!({
+                "OMSId": 1,
+                "AccountId": 185,
+                "InstrumentId": 1,
+                "Side": 1,
+                "OrderType": 2,
+                "Quantity": 0.25,
+                "LimitPrice": 5711.8,
+                "StopPrice": 0.0,
+                "TrailingAmount": 0.0,
+                "LimitOffset": 0.0,
+                "PegPriceType": 1,
+                "UseDisplayQuantity": true,
+                "DisplayQuantity": 0.05,
+                "TimeInForce": 1,
+                "PostOnly": false,
+                "ClientOrderId": 42
+            })
+        );
+
+        let replace = order.to_replace_payload(1, 185, OrderId(6713));
+        assert_eq!(replace["OrderIdToReplace"], 6713);
+        assert_eq!(replace["ClientOrdId"], 42);
+        assert!(replace.get("ClientOrderId").is_none());
+    }
+}
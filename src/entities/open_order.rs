@@ -0,0 +1,80 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::order_types::OrderId;
+
+/// An order as returned by GetOpenOrders, GetOrderStatus and GetOrdersHistory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenOrder {
+    #[serde(rename = "OMSId")]
+    pub oms_id: u64,
+    #[serde(rename = "OrderId")]
+    pub order_id: OrderId,
+    #[serde(rename = "ClientOrderId", default)]
+    pub client_order_id: u64,
+    #[serde(rename = "Account")]
+    pub account_id: u64,
+    #[serde(rename = "Instrument")]
+    pub instrument_id: u64,
+    #[serde(rename = "Side")]
+    pub side: String,
+    #[serde(rename = "OrderType")]
+    pub order_type: String,
+    #[serde(rename = "OrderState")]
+    pub order_state: String,
+    #[serde(rename = "OrderRevision", default)]
+    pub order_revision: u64,
+    #[serde(rename = "Price")]
+    pub price: Decimal,
+    #[serde(rename = "StopPrice", default)]
+    pub stop_price: Decimal,
+    #[serde(rename = "Quantity")]
+    pub quantity: Decimal,
+    #[serde(rename = "DisplayQuantity", default)]
+    pub display_quantity: Decimal,
+    #[serde(rename = "OrigQuantity")]
+    pub orig_quantity: Decimal,
+    #[serde(rename = "QuantityExecuted")]
+    pub quantity_executed: Decimal,
+    #[serde(rename = "AvgPrice", default)]
+    pub avg_price: Decimal,
+    #[serde(rename = "ReceiveTime")]
+    pub receive_time: u64,
+    #[serde(rename = "LastUpdatedTime", default)]
+    pub last_updated_time: u64,
+    #[serde(rename = "ChangeReason", default)]
+    pub change_reason: Option<String>,
+    #[serde(rename = "RejectReason", default)]
+    pub reject_reason: Option<String>,
+    #[serde(rename = "CancelReason", default)]
+    pub cancel_reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_open_order() {
+        let order: OpenOrder = serde_json::from_str(
+            r#"{"Side":"Buy","OrderId":6713,"Price":5700.5,"Quantity":0.25,
+                "DisplayQuantity":0.25,"Instrument":1,"Account":185,"AccountName":"trader",
+                "OrderType":"Limit","ClientOrderId":42,"OrderState":"Working",
+                "ReceiveTime":1718007168597,"ReceiveTimeTicks":638536039685970000,
+                "LastUpdatedTime":1718007168600,"LastUpdatedTimeTicks":638536039686000000,
+                "OrigQuantity":0.25,"QuantityExecuted":0.0,"GrossValueExecuted":0.0,
+                "ExecutableValue":0.0,"AvgPrice":0.0,"CounterPartyId":0,
+                "ChangeReason":"NewInputAccepted","OrigOrderId":6713,"OrigClOrdId":0,
+                "EnteredBy":170,"UserName":"trader","IsQuote":false,"InsideAsk":5711.8,
+                "InsideAskSize":8.13439401,"InsideBid":5711.7,"InsideBidSize":0.007498,
+                "LastTradePrice":5711.7,"RejectReason":"","IsLockedIn":false,
+                "CancelReason":"","OrderFlag":"AddedToBook","UseMargin":false,"StopPrice":0.0,
+                "PegPriceType":"Last","PegOffset":0.0,"PegLimitOffset":0.0,"OMSId":1}"#,
+        )
+        .unwrap();
+        assert_eq!(order.order_id, OrderId(6713));
+        assert_eq!(order.client_order_id, 42);
+        assert_eq!(order.price, Decimal::new(57005, 1));
+        assert_eq!(order.order_state, "Working");
+    }
+}
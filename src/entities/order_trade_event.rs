@@ -0,0 +1,66 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::order_types::{ClientOrderId, OrderId};
+
+/// A fill against one of the account's orders, sent as an OrderTradeEvent and
+/// returned by GetAccountTrades and GetTradesHistory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderTradeEvent {
+    #[serde(rename = "OMSId", default)]
+    pub oms_id: u64,
+    #[serde(rename = "TradeId")]
+    pub trade_id: u64,
+    #[serde(rename = "OrderId")]
+    pub order_id: OrderId,
+    #[serde(rename = "ClientOrderId", default)]
+    pub client_order_id: u64,
+    #[serde(rename = "AccountId")]
+    pub account_id: u64,
+    #[serde(rename = "InstrumentId")]
+    pub instrument_id: u64,
+    #[serde(rename = "Side")]
+    pub side: String,
+    #[serde(rename = "Quantity")]
+    pub quantity: Decimal,
+    #[serde(rename = "RemainingQuantity", default)]
+    pub remaining_quantity: Decimal,
+    #[serde(rename = "Price")]
+    pub price: Decimal,
+    #[serde(rename = "Value", default)]
+    pub value: Decimal,
+    #[serde(rename = "Fee", default)]
+    pub fee: Decimal,
+    #[serde(rename = "FeeProductId", default)]
+    pub fee_product_id: u64,
+    #[serde(rename = "TradeTimeMS", default)]
+    pub trade_time_ms: u64,
+}
+
+impl OrderTradeEvent {
+    pub fn client_order_id(&self) -> ClientOrderId {
+        ClientOrderId(self.client_order_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_order_trade_event() {
+        let event: OrderTradeEvent = serde_json::from_str(
+            r#"{"OMSId":1,"TradeId":213,"OrderId":6713,"AccountId":185,"ClientOrderId":42,
+                "InstrumentId":1,"Side":"Buy","Quantity":0.1,"RemainingQuantity":0.15,
+                "Price":5711.8,"Value":571.18,"TradeTime":638536039685970000,
+                "ContraAcctId":12,"OrderTradeRevision":1,"Direction":"NoChange",
+                "IsBlockTrade":false,"Fee":0.0001,"FeeProductId":1,"OrderOriginator":170,
+                "UserName":"trader","TradeTimeMS":1718007168597,"MakerTaker":"Taker"}"#,
+        )
+        .unwrap();
+        assert_eq!(event.order_id, OrderId(6713));
+        assert_eq!(event.client_order_id(), ClientOrderId(42));
+        assert_eq!(event.quantity, Decimal::new(1, 1));
+        assert_eq!(event.remaining_quantity, Decimal::new(15, 2));
+    }
+}
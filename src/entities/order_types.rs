@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Exchange-assigned identifier of an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OrderId(pub u64);
+
+/// Identifier the client attaches to an order so it can recognise it later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ClientOrderId(pub u64);
+
+impl fmt::Display for OrderId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for ClientOrderId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Either identifier of an order, for calls that accept both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderRef {
+    Id(OrderId),
+    Client(ClientOrderId),
+}
+
+impl From<OrderId> for OrderRef {
+    fn from(order_id: OrderId) -> Self {
+        OrderRef::Id(order_id)
+    }
+}
+
+impl From<ClientOrderId> for OrderRef {
+    fn from(client_order_id: ClientOrderId) -> Self {
+        OrderRef::Client(client_order_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum OrderType {
+    Market,
+    Limit,
+    StopMarket,
+    StopLimit,
+    TrailingStopMarket,
+    TrailingStopLimit,
+}
+
+impl OrderType {
+    // Whether the order carries a LimitPrice
+    pub fn has_limit_price(&self) -> bool {
+        matches!(self, OrderType::Limit | OrderType::StopLimit)
+    }
+
+    // Whether the order carries a StopPrice
+    pub fn has_stop_price(&self) -> bool {
+        matches!(self, OrderType::StopMarket | OrderType::StopLimit)
+    }
+
+    pub fn is_trailing(&self) -> bool {
+        matches!(
+            self,
+            OrderType::TrailingStopMarket | OrderType::TrailingStopLimit
+        )
+    }
+}
+
+impl TryFrom<u8> for OrderType {
+    type Error = String;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        match code {
+            1 => Ok(OrderType::Market),
+            2 => Ok(OrderType::Limit),
+            3 => Ok(OrderType::StopMarket),
+            4 => Ok(OrderType::StopLimit),
+            5 => Ok(OrderType::TrailingStopMarket),
+            6 => Ok(OrderType::TrailingStopLimit),
+            _ => Err(format!("unknown order type: {}", code)),
+        }
+    }
+}
+
+impl From<OrderType> for u8 {
+    fn from(order_type: OrderType) -> Self {
+        match order_type {
+            OrderType::Market => 1,
+            OrderType::Limit => 2,
+            OrderType::StopMarket => 3,
+            OrderType::StopLimit => 4,
+            OrderType::TrailingStopMarket => 5,
+            OrderType::TrailingStopLimit => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum TimeInForce {
+    // Good till canceled
+    #[default]
+    Gtc,
+    // Immediate or cancel
+    Ioc,
+    // Fill or kill
+    Fok,
+}
+
+impl TryFrom<u8> for TimeInForce {
+    type Error = String;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        match code {
+            1 => Ok(TimeInForce::Gtc),
+            3 => Ok(TimeInForce::Ioc),
+            4 => Ok(TimeInForce::Fok),
+            _ => Err(format!("unknown time in force: {}", code)),
+        }
+    }
+}
+
+impl From<TimeInForce> for u8 {
+    fn from(time_in_force: TimeInForce) -> Self {
+        match time_in_force {
+            TimeInForce::Gtc => 1,
+            TimeInForce::Ioc => 3,
+            TimeInForce::Fok => 4,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// The account's balance in one product, from GetAccountPositions or an
+/// AccountPositionEvent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    #[serde(rename = "OMSId", default)]
+    pub oms_id: u64,
+    #[serde(rename = "AccountId")]
+    pub account_id: u64,
+    #[serde(rename = "ProductSymbol")]
+    pub product_symbol: String,
+    #[serde(rename = "ProductId")]
+    pub product_id: u64,
+    #[serde(rename = "Amount")]
+    pub amount: Decimal,
+    // Part of the amount reserved by working orders and pending withdrawals
+    #[serde(rename = "Hold")]
+    pub hold: Decimal,
+    #[serde(rename = "PendingDeposits", default)]
+    pub pending_deposits: Decimal,
+    #[serde(rename = "PendingWithdraws", default)]
+    pub pending_withdraws: Decimal,
+    #[serde(rename = "TotalDayDeposits", default)]
+    pub total_day_deposits: Decimal,
+    #[serde(rename = "TotalDayWithdraws", default)]
+    pub total_day_withdraws: Decimal,
+}
+
+impl Position {
+    // Amount free to trade or withdraw
+    pub fn available(&self) -> Decimal {
+        self.amount - self.hold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_position() {
+        let position: Position = serde_json::from_str(
+            r#"{"OMSId":1,"AccountId":185,"ProductSymbol":"BTC","ProductId":1,
+                "Amount":1.5,"Hold":0.25,"PendingDeposits":0,"PendingWithdraws":0.1,
+                "TotalDayDeposits":0,"TotalDayWithdraws":0,"TotalMonthWithdraws":0}"#,
+        )
+        .unwrap();
+        assert_eq!(position.product_symbol, "BTC");
+        assert_eq!(position.available(), Decimal::new(125, 2));
+        assert_eq!(position.pending_withdraws, Decimal::new(1, 1));
+    }
+}
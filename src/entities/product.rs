@@ -0,0 +1,40 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// A currency or asset held and traded on NDAX, as returned by GetProducts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Product {
+    #[serde(rename = "ProductId")]
+    pub id: u64,
+    #[serde(rename = "Product")]
+    pub symbol: String,
+    #[serde(rename = "ProductFullName", default)]
+    pub full_name: String,
+    // e.g. NationalCurrency, CryptoCurrency
+    #[serde(rename = "ProductType", default)]
+    pub product_type: String,
+    #[serde(rename = "DecimalPlaces", default)]
+    pub decimal_places: u32,
+    #[serde(rename = "TickSize", default)]
+    pub tick_size: Decimal,
+    #[serde(rename = "IsDisabled", default)]
+    pub is_disabled: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_product() {
+        let product: Product = serde_json::from_str(
+            r#"{"OMSId":1,"ProductId":3,"Product":"CAD","ProductFullName":"Canadian Dollar",
+                "ProductType":"NationalCurrency","DecimalPlaces":2,"TickSize":0.01,
+                "NoFees":false,"IsDisabled":false,"MarginEnabled":false}"#,
+        )
+        .unwrap();
+        assert_eq!(product.symbol, "CAD");
+        assert_eq!(product.decimal_places, 2);
+        assert_eq!(product.tick_size, Decimal::new(1, 2));
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::order_types::OrderId;
+use crate::error::NdaxError;
+
+// Status NDAX puts in the reply when it takes the order
+const ACCEPTED_STATUS: &str = "Accepted";
+
+/// The reply to SendOrder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SendOrderResponse {
+    pub status: String,
+    #[serde(default)]
+    pub errormsg: Option<String>,
+    #[serde(rename = "OrderId")]
+    pub order_id: OrderId,
+}
+
+impl SendOrderResponse {
+    pub fn is_accepted(&self) -> bool {
+        self.status == ACCEPTED_STATUS
+    }
+
+    // Turns a rejected order into an error
+    pub fn into_result(self) -> Result<Self, NdaxError> {
+        if self.is_accepted() {
+            return Ok(self);
+        }
+        let message = match self.errormsg {
+            Some(message) if !message.is_empty() => message,
+            _ => self.status,
+        };
+        Err(NdaxError::OrderRejected(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_send_order_response() {
+        let response: SendOrderResponse =
+            serde_json::from_str(r#"{"status":"Accepted","errormsg":"","OrderId":6713}"#).unwrap();
+        assert!(response.is_accepted());
+        assert_eq!(response.order_id, OrderId(6713));
+
+        let response: SendOrderResponse = serde_json::from_str(
+            r#"{"status":"Rejected","errormsg":"Not_Enough_Funds","OrderId":0}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            response.into_result(),
+            Err(NdaxError::OrderRejected(message)) if message == "Not_Enough_Funds"
+        ));
+    }
+}
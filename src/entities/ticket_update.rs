@@ -0,0 +1,49 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// A deposit or withdrawal ticket, as sent in DepositTicketUpdateEvent and
+/// WithdrawTicketUpdateEvent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TicketUpdate {
+    #[serde(rename = "OMSId", default)]
+    pub oms_id: u64,
+    #[serde(rename = "AccountId")]
+    pub account_id: u64,
+    #[serde(rename = "TicketNumber")]
+    pub ticket_number: u64,
+    #[serde(rename = "AssetId")]
+    pub asset_id: u64,
+    #[serde(rename = "AssetName", default)]
+    pub asset_name: String,
+    #[serde(rename = "Amount")]
+    pub amount: Decimal,
+    #[serde(rename = "FeeAmt", default)]
+    pub fee_amount: Decimal,
+    // e.g. New, Pending, FullyProcessed, Rejected
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "CreatedTimestamp", default)]
+    pub created_timestamp: Option<String>,
+    #[serde(rename = "LastUpdateTimeStamp", default)]
+    pub last_update_timestamp: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_ticket_update() {
+        let ticket: TicketUpdate = serde_json::from_str(
+            r#"{"AssetManagerId":1,"AccountId":185,"AssetId":3,"AssetName":"Canadian Dollar",
+                "Amount":500.0,"OMSId":1,"RequestCode":"b5b1e1f2","RequestUser":170,
+                "OperatorId":1,"Status":"FullyProcessed","FeeAmt":0.0,"TicketNumber":1021,
+                "CreatedTimestamp":"2024-06-10T08:19:28.597Z",
+                "LastUpdateTimeStamp":"2024-06-10T08:21:02.100Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(ticket.ticket_number, 1021);
+        assert_eq!(ticket.amount, Decimal::new(500, 0));
+        assert_eq!(ticket.status, "FullyProcessed");
+    }
+}
@@ -1,15 +1,22 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::entities::decimal_number;
+
+/// A public trade, as sent in SubscribeTrades replies and TradeDataUpdateEvent.
+///
+/// Deserializes from the gateway's array form; serializes with named columns for CSV.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TradeEvent {
     #[serde(rename = "TradeId")]
     pub trade_id: u64,
     #[serde(rename = "ProductPairCode")]
     pub instrument_id: u64,
     #[serde(rename = "Quantity")]
-    pub quantity: f64,
+    pub quantity: Decimal,
     #[serde(rename = "Price")]
-    pub price: f64,
+    pub price: Decimal,
     #[serde(rename = "Order1")]
     pub order_id_1: u64,
     #[serde(rename = "Order2")]
@@ -23,5 +30,24 @@ pub struct TradeEvent {
     #[serde(rename = "isBlockTrade")]
     pub is_block_trade: u8,
     #[serde(rename = "orderClientId")]
-    pub client_id: u8,
+    pub client_id: u64,
+}
+
+impl TradeEvent {
+    // The array form the gateway sends, e.g. to rebuild a TradeDataUpdateEvent
+    pub fn to_row(&self) -> Value {
+        json!([
+            self.trade_id,
+            self.instrument_id,
+            decimal_number(self.quantity),
+            decimal_number(self.price),
+            self.order_id_1,
+            self.order_id_2,
+            self.timestamp,
+            self.side,
+            self.taker_side,
+            self.is_block_trade,
+            self.client_id
+        ])
+    }
 }
@@ -0,0 +1,206 @@
+use arrow_schema::ArrowError;
+use parquet::errors::ParquetError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite;
+
+use crate::entities::generic_response::GenericResponse;
+
+// NDAX errorcode returned when the session lacks permission for a call
+const NOT_AUTHORIZED_ERROR_CODE: i64 = 20;
+
+/// Everything that can go wrong talking to NDAX.
+#[derive(Debug)]
+pub enum NdaxError {
+    // The REST request could not be sent or its body could not be read
+    Http(reqwest::Error),
+    // The WebSocket connection failed
+    WebSocket(Box<tungstenite::Error>),
+    // The WebSocket connection went away before a reply arrived
+    ConnectionClosed,
+    // No reply to a gateway call within the allotted time
+    Timeout {
+        function_name: String,
+        timeout: Duration,
+    },
+    // The REST API answered with a non-success status
+    HttpStatus {
+        status: u16,
+        body: String,
+    },
+    // The response was not the JSON we expected
+    Json(serde_json::Error),
+    // NDAX reported the call as failed
+    Exchange {
+        code: i64,
+        message: String,
+        detail: Option<String>,
+    },
+    Authentication(String),
+    // The session token was missing, expired or no longer valid
+    SessionRejected(String),
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
+    // An order failed validation before it was sent
+    InvalidOrder(String),
+    // NDAX refused an order
+    OrderRejected(String),
+    // The catalogue has no instrument with this symbol or id
+    UnknownInstrument(String),
+    // A frame or payload did not have the expected shape
+    MalformedMessage(String),
+    Config(String),
+    Io(io::Error),
+    Csv(csv::Error),
+    Parquet(ParquetError),
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for NdaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NdaxError::Http(e) => write!(f, "HTTP transport error: {}", e),
+            NdaxError::WebSocket(e) => write!(f, "WebSocket error: {}", e),
+            NdaxError::ConnectionClosed => write!(f, "WebSocket connection closed"),
+            NdaxError::Timeout {
+                function_name,
+                timeout,
+            } => write!(f, "{} timed out after {:?}", function_name, timeout),
+            NdaxError::HttpStatus { status, body } => write!(f, "HTTP {}: {}", status, body),
+            NdaxError::Json(e) => write!(f, "JSON decode error: {}", e),
+            NdaxError::Exchange {
+                code,
+                message,
+                detail,
+            } => match detail {
+                Some(detail) => write!(f, "exchange error {}: {} ({})", code, message, detail),
+                None => write!(f, "exchange error {}: {}", code, message),
+            },
+            NdaxError::Authentication(message) => write!(f, "authentication failed: {}", message),
+            NdaxError::SessionRejected(message) => write!(f, "session rejected: {}", message),
+            NdaxError::RateLimited {
+                retry_after: Some(retry_after),
+            } => write!(f, "rate limited, retry after {:?}", retry_after),
+            NdaxError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            NdaxError::InvalidOrder(message) => write!(f, "invalid order: {}", message),
+            NdaxError::OrderRejected(message) => write!(f, "order rejected: {}", message),
+            NdaxError::UnknownInstrument(instrument) => {
+                write!(f, "unknown instrument: {}", instrument)
+            }
+            NdaxError::MalformedMessage(message) => write!(f, "malformed message: {}", message),
+            NdaxError::Config(message) => write!(f, "configuration error: {}", message),
+            NdaxError::Io(e) => write!(f, "I/O error: {}", e),
+            NdaxError::Csv(e) => write!(f, "CSV error: {}", e),
+            NdaxError::Parquet(e) => write!(f, "Parquet error: {}", e),
+            NdaxError::Sqlite(e) => write!(f, "SQLite error: {}", e),
+        }
+    }
+}
+
+impl Error for NdaxError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NdaxError::Http(e) => Some(e),
+            NdaxError::WebSocket(e) => Some(e.as_ref()),
+            NdaxError::Json(e) => Some(e),
+            NdaxError::Io(e) => Some(e),
+            NdaxError::Csv(e) => Some(e),
+            NdaxError::Parquet(e) => Some(e),
+            NdaxError::Sqlite(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for NdaxError {
+    fn from(e: reqwest::Error) -> Self {
+        NdaxError::Http(e)
+    }
+}
+
+impl From<tungstenite::Error> for NdaxError {
+    fn from(e: tungstenite::Error) -> Self {
+        NdaxError::WebSocket(Box::new(e))
+    }
+}
+
+impl From<serde_json::Error> for NdaxError {
+    fn from(e: serde_json::Error) -> Self {
+        NdaxError::Json(e)
+    }
+}
+
+impl From<io::Error> for NdaxError {
+    fn from(e: io::Error) -> Self {
+        NdaxError::Io(e)
+    }
+}
+
+impl From<csv::Error> for NdaxError {
+    fn from(e: csv::Error) -> Self {
+        NdaxError::Csv(e)
+    }
+}
+
+impl From<ParquetError> for NdaxError {
+    fn from(e: ParquetError) -> Self {
+        NdaxError::Parquet(e)
+    }
+}
+
+impl From<ArrowError> for NdaxError {
+    fn from(e: ArrowError) -> Self {
+        NdaxError::Parquet(ParquetError::from(e))
+    }
+}
+
+impl From<rusqlite::Error> for NdaxError {
+    fn from(e: rusqlite::Error) -> Self {
+        NdaxError::Sqlite(e)
+    }
+}
+
+impl From<GenericResponse> for NdaxError {
+    fn from(response: GenericResponse) -> Self {
+        let message = response.errormsg.unwrap_or_default();
+        if response.errorcode == NOT_AUTHORIZED_ERROR_CODE {
+            return NdaxError::Authentication(message);
+        }
+        NdaxError::Exchange {
+            code: response.errorcode,
+            message,
+            detail: response.detail,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_response_maps_to_error() {
+        let response: GenericResponse = serde_json::from_str(
+            r#"{"result":false,"errormsg":"Not Authorized","errorcode":20,"detail":null}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            NdaxError::from(response),
+            NdaxError::Authentication(message) if message == "Not Authorized"
+        ));
+
+        let response: GenericResponse = serde_json::from_str(
+            r#"{"result":false,"errormsg":"Invalid Request","errorcode":100,"detail":"Not_Enough_Funds"}"#,
+        )
+        .unwrap();
+        let error = NdaxError::from(response);
+        assert!(matches!(error, NdaxError::Exchange { code: 100, .. }));
+        assert_eq!(
+            error.to_string(),
+            "exchange error 100: Invalid Request (Not_Enough_Funds)"
+        );
+    }
+}
@@ -1,8 +1,21 @@
 use reqwest::Client;
-use std::error::Error;
+use std::collections::HashMap;
+use std::time::Duration;
 
 use crate::constants;
+use crate::entities::asset::Asset;
+use crate::entities::candle::Candle;
+use crate::entities::instrument::Instrument;
+use crate::entities::level1::Level1;
+use crate::entities::level2_entry::Level2Entry;
+use crate::entities::pong::Pong;
+use crate::entities::product::Product;
+use crate::entities::trade_event::TradeEvent;
+use crate::error::NdaxError;
+use crate::rest::parse_response;
+use crate::utc;
 
+/// Client for the public REST endpoints.
 pub struct ExchangeManager {
     api_url: String,
     client: Client,
@@ -15,29 +28,118 @@ impl ExchangeManager {
             client: Client::new(),
         }
     }
-    pub async fn ping(&self) -> Result<serde_json::Value, Box<dyn Error>> {
+    pub async fn ping(&self) -> Result<Pong, NdaxError> {
         let url = format!("{}{}", self.api_url, constants::PING);
+        let response = self.client.get(&url).send().await?;
+
+        parse_response::<Pong>(response).await
+    }
+
+    // Assets keyed by their symbol, e.g. "BTC"
+    pub async fn get_assets(&self) -> Result<HashMap<String, Asset>, NdaxError> {
+        let url = format!("{}{}", self.api_url, constants::ASSETS);
+        let response = self.client.get(&url).send().await?;
+
+        parse_response::<HashMap<String, Asset>>(response).await
+    }
+
+    pub async fn get_instruments(&self) -> Result<Vec<Instrument>, NdaxError> {
+        let url = format!("{}{}", self.api_url, constants::GET_INSTRUMENTS);
         let response = self
             .client
             .get(&url)
+            .query(&[("OMSId", "1")])
             .send()
-            .await?
-            .json::<serde_json::Value>()
             .await?;
 
-        Ok(response)
+        parse_response::<Vec<Instrument>>(response).await
     }
 
-    pub async fn get_assets(&self) -> Result<serde_json::Value, Box<dyn Error>> {
-        let url = format!("{}{}", self.api_url, constants::ASSETS);
+    pub async fn get_instrument(&self, instrument_id: u64) -> Result<Instrument, NdaxError> {
+        let query_params = [("OMSId", "1"), ("InstrumentId", &instrument_id.to_string())];
+
+        let url = format!("{}{}", self.api_url, constants::GET_INSTRUMENT);
+        let response = self.client.get(&url).query(&query_params).send().await?;
+
+        parse_response::<Instrument>(response).await
+    }
+
+    // Up to `depth` levels per side, as rows ready for OrderBook::apply_snapshot
+    pub async fn get_l2_snapshot(
+        &self,
+        instrument_id: u64,
+        depth: usize,
+    ) -> Result<Vec<Level2Entry>, NdaxError> {
+        let query_params = [
+            ("OMSId", "1"),
+            ("InstrumentId", &instrument_id.to_string()),
+            ("Depth", &depth.to_string()),
+        ];
+
+        let url = format!("{}{}", self.api_url, constants::GET_L2_SNAPSHOT);
+        let response = self.client.get(&url).query(&query_params).send().await?;
+
+        parse_response::<Vec<Level2Entry>>(response).await
+    }
+
+    pub async fn get_level1(&self, instrument_id: u64) -> Result<Level1, NdaxError> {
+        let query_params = [("OMSId", "1"), ("InstrumentId", &instrument_id.to_string())];
+
+        let url = format!("{}{}", self.api_url, constants::GET_LEVEL1);
+        let response = self.client.get(&url).query(&query_params).send().await?;
+
+        parse_response::<Level1>(response).await
+    }
+
+    /// OHLC bars of `interval` length between two Unix times in seconds.
+    pub async fn get_ticker_history(
+        &self,
+        instrument_id: u64,
+        interval: Duration,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<Candle>, NdaxError> {
+        let query_params = [
+            ("OMSId", "1".to_string()),
+            ("InstrumentId", instrument_id.to_string()),
+            ("Interval", interval.as_secs().to_string()),
+            ("FromDate", utc::format_iso8601(from)),
+            ("ToDate", utc::format_iso8601(to)),
+        ];
+
+        let url = format!("{}{}", self.api_url, constants::GET_TICKER_HISTORY);
+        let response = self.client.get(&url).query(&query_params).send().await?;
+
+        parse_response::<Vec<Candle>>(response).await
+    }
+
+    // The most recent public trades, oldest first
+    pub async fn get_last_trades(
+        &self,
+        instrument_id: u64,
+        count: usize,
+    ) -> Result<Vec<TradeEvent>, NdaxError> {
+        let query_params = [
+            ("OMSId", "1"),
+            ("InstrumentId", &instrument_id.to_string()),
+            ("Count", &count.to_string()),
+        ];
+
+        let url = format!("{}{}", self.api_url, constants::GET_LAST_TRADES);
+        let response = self.client.get(&url).query(&query_params).send().await?;
+
+        parse_response::<Vec<TradeEvent>>(response).await
+    }
+
+    pub async fn get_products(&self) -> Result<Vec<Product>, NdaxError> {
+        let url = format!("{}{}", self.api_url, constants::GET_PRODUCTS);
         let response = self
             .client
             .get(&url)
+            .query(&[("OMSId", "1")])
             .send()
-            .await?
-            .json::<serde_json::Value>()
             .await?;
 
-        Ok(response)
+        parse_response::<Vec<Product>>(response).await
     }
 }
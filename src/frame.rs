@@ -0,0 +1,183 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use crate::error::NdaxError;
+
+// Message types used in the "m" field of every gateway frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageType {
+    Request,
+    Reply,
+    SubscribeToEvent,
+    Event,
+    UnsubscribeFromEvent,
+    Error,
+}
+
+impl MessageType {
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(MessageType::Request),
+            1 => Some(MessageType::Reply),
+            2 => Some(MessageType::SubscribeToEvent),
+            3 => Some(MessageType::Event),
+            4 => Some(MessageType::UnsubscribeFromEvent),
+            5 => Some(MessageType::Error),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> u8 {
+        match self {
+            MessageType::Request => 0,
+            MessageType::Reply => 1,
+            MessageType::SubscribeToEvent => 2,
+            MessageType::Event => 3,
+            MessageType::UnsubscribeFromEvent => 4,
+            MessageType::Error => 5,
+        }
+    }
+}
+
+// Wire representation of a frame, where "o" is itself a JSON document encoded as a string
+#[derive(Debug, Serialize, Deserialize)]
+struct RawFrame {
+    m: u8,
+    i: u64,
+    n: String,
+    #[serde(default)]
+    o: String,
+}
+
+/// A single message exchanged with the NDAX WebSocket gateway.
+///
+/// The payload is held already decoded; the double encoding of the "o" field
+/// is handled once by `encode` and `decode`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub message_type: MessageType,
+    pub sequence: u64,
+    pub function_name: String,
+    pub payload: Value,
+}
+
+impl Frame {
+    pub fn new(
+        message_type: MessageType,
+        sequence: u64,
+        function_name: &str,
+        payload: Value,
+    ) -> Self {
+        Frame {
+            message_type,
+            sequence,
+            function_name: function_name.to_string(),
+            payload,
+        }
+    }
+
+    pub fn request(sequence: u64, function_name: &str, payload: Value) -> Self {
+        Frame::new(MessageType::Request, sequence, function_name, payload)
+    }
+
+    // Serializes the frame into the text sent over the socket
+    pub fn encode(&self) -> String {
+        let o = match &self.payload {
+            Value::Null => String::new(),
+            payload => payload.to_string(),
+        };
+        let raw = RawFrame {
+            m: self.message_type.code(),
+            i: self.sequence,
+            n: self.function_name.clone(),
+            o,
+        };
+        serde_json::to_string(&raw).expect("frame serialization cannot fail")
+    }
+
+    // Parses a text message received from the socket
+    pub fn decode(text: &str) -> Result<Self, NdaxError> {
+        let raw: RawFrame = serde_json::from_str(text)?;
+        let message_type = MessageType::from_code(raw.m).ok_or_else(|| {
+            NdaxError::MalformedMessage(format!("unknown message type: {}", raw.m))
+        })?;
+        let payload = if raw.o.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&raw.o)?
+        };
+
+        Ok(Frame {
+            message_type,
+            sequence: raw.i,
+            function_name: raw.n,
+            payload,
+        })
+    }
+
+    // Deserializes the payload into a concrete type
+    pub fn payload_as<T: DeserializeOwned>(&self) -> Result<T, NdaxError> {
+        Ok(T::deserialize(&self.payload)?)
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_level2_snapshot() {
+        let text = json!({
+            "i": 1,
+            "m": 1,
+            "n": "SubscribeLevel2",
+            "o": "[[1,1,1718003785385,0,5711.70000,1,5711.80000,1,8.13439401,1],
+                  [11,1,1718003785385,0,5711.70000,1,5711.70000,1,0.00749800,0]]"
+        })
+        .to_string();
+
+        let frame = Frame::decode(&text).unwrap();
+        assert_eq!(frame.message_type, MessageType::Reply);
+        assert_eq!(frame.sequence, 1);
+        assert_eq!(frame.function_name, "SubscribeLevel2");
+
+        let rows: Vec<Vec<Value>> = frame.payload_as().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][0], json!(11));
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let frame = Frame::request(
+            7,
+            "SubscribeTrades",
+            json!({"OMSId": 1, "InstrumentId": 1, "IncludeLastCount": 10}),
+        );
+        let text = frame.encode();
+
+        let raw: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(raw["m"], json!(0));
+        assert_eq!(raw["i"], json!(7));
+        assert!(raw["o"].is_string());
+
+        assert_eq!(Frame::decode(&text).unwrap(), frame);
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_message_type() {
+        let text = json!({"m": 9, "i": 1, "n": "Ping", "o": "{}"}).to_string();
+        assert!(matches!(
+            Frame::decode(&text),
+            Err(NdaxError::MalformedMessage(_))
+        ));
+    }
+}
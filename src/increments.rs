@@ -0,0 +1,104 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Tick size and lot size an instrument trades in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Increments {
+    pub price_increment: Decimal,
+    pub quantity_increment: Decimal,
+    pub min_quantity: Decimal,
+}
+
+impl Increments {
+    pub fn new(
+        price_increment: Decimal,
+        quantity_increment: Decimal,
+        min_quantity: Decimal,
+    ) -> Self {
+        Increments {
+            price_increment,
+            quantity_increment,
+            min_quantity,
+        }
+    }
+
+    // Whether the price sits exactly on a tick
+    pub fn is_valid_price(&self, price: Decimal) -> bool {
+        price > Decimal::ZERO && is_multiple_of(price, self.price_increment)
+    }
+
+    // Whether the quantity is a whole number of lots and at least the minimum
+    pub fn is_valid_quantity(&self, quantity: Decimal) -> bool {
+        quantity >= self.min_quantity && is_multiple_of(quantity, self.quantity_increment)
+    }
+
+    // Rounds a price down to the tick below, e.g. for a bid
+    pub fn round_price_down(&self, price: Decimal) -> Decimal {
+        round_to_increment(
+            price,
+            self.price_increment,
+            RoundingStrategy::ToNegativeInfinity,
+        )
+    }
+
+    // Rounds a price up to the tick above, e.g. for an ask
+    pub fn round_price_up(&self, price: Decimal) -> Decimal {
+        round_to_increment(
+            price,
+            self.price_increment,
+            RoundingStrategy::ToPositiveInfinity,
+        )
+    }
+
+    // Rounds a quantity down to a whole number of lots
+    pub fn round_quantity(&self, quantity: Decimal) -> Decimal {
+        round_to_increment(quantity, self.quantity_increment, RoundingStrategy::ToZero)
+    }
+}
+
+fn is_multiple_of(value: Decimal, increment: Decimal) -> bool {
+    increment.is_zero() || (value % increment).is_zero()
+}
+
+fn round_to_increment(value: Decimal, increment: Decimal, strategy: RoundingStrategy) -> Decimal {
+    if increment.is_zero() {
+        return value;
+    }
+    let lots = (value / increment).round_dp_with_strategy(0, strategy);
+    (lots * increment).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn btc_cad() -> Increments {
+        Increments::new(dec("0.1"), dec("0.00000001"), dec("0.0001"))
+    }
+
+    #[test]
+    fn test_validates_ticks_and_lots() {
+        let increments = btc_cad();
+        assert!(increments.is_valid_price(dec("5711.8")));
+        assert!(!increments.is_valid_price(dec("5711.85")));
+        assert!(increments.is_valid_quantity(dec("0.00749800")));
+        assert!(!increments.is_valid_quantity(dec("0.000000001")));
+        assert!(!increments.is_valid_quantity(dec("0.00001")));
+    }
+
+    #[test]
+    fn test_rounds_to_increments() {
+        let increments = btc_cad();
+        assert_eq!(increments.round_price_down(dec("5711.87")), dec("5711.8"));
+        assert_eq!(increments.round_price_up(dec("5711.81")), dec("5711.9"));
+        assert_eq!(increments.round_price_up(dec("5711.8")), dec("5711.8"));
+        assert_eq!(
+            increments.round_quantity(dec("1.123456789")),
+            dec("1.12345678")
+        );
+    }
+}
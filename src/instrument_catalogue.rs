@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use crate::entities::instrument::Instrument;
+use crate::error::NdaxError;
+use crate::exchange_manager::ExchangeManager;
+use crate::increments::Increments;
+
+/// Instruments by id and symbol, loaded once from GetInstruments.
+///
+/// Resolves symbols such as "BTCCAD" to the InstrumentId subscriptions need,
+/// and supplies the increments `OrderManager::send_order` validates against.
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentCatalogue {
+    instruments: HashMap<u64, Instrument>,
+    ids_by_symbol: HashMap<String, u64>,
+}
+
+impl InstrumentCatalogue {
+    pub fn new(instruments: Vec<Instrument>) -> Self {
+        let ids_by_symbol = instruments
+            .iter()
+            .map(|instrument| (instrument.symbol.to_uppercase(), instrument.id))
+            .collect();
+        let instruments = instruments
+            .into_iter()
+            .map(|instrument| (instrument.id, instrument))
+            .collect();
+        InstrumentCatalogue {
+            instruments,
+            ids_by_symbol,
+        }
+    }
+
+    pub async fn load(exchange_manager: &ExchangeManager) -> Result<Self, NdaxError> {
+        Ok(InstrumentCatalogue::new(
+            exchange_manager.get_instruments().await?,
+        ))
+    }
+
+    pub fn get(&self, instrument_id: u64) -> Option<&Instrument> {
+        self.instruments.get(&instrument_id)
+    }
+
+    // Looks an instrument up by symbol, ignoring case
+    pub fn by_symbol(&self, symbol: &str) -> Option<&Instrument> {
+        self.ids_by_symbol
+            .get(&symbol.to_uppercase())
+            .and_then(|instrument_id| self.instruments.get(instrument_id))
+    }
+
+    // The InstrumentId for a symbol like "BTCCAD"
+    pub fn resolve(&self, symbol: &str) -> Result<u64, NdaxError> {
+        self.by_symbol(symbol)
+            .map(|instrument| instrument.id)
+            .ok_or_else(|| NdaxError::UnknownInstrument(symbol.to_string()))
+    }
+
+    // Tick and lot sizes to validate orders on an instrument against
+    pub fn increments(&self, instrument_id: u64) -> Result<Increments, NdaxError> {
+        self.get(instrument_id)
+            .map(Instrument::increments)
+            .ok_or_else(|| NdaxError::UnknownInstrument(instrument_id.to_string()))
+    }
+
+    pub fn instruments(&self) -> impl Iterator<Item = &Instrument> {
+        self.instruments.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::catalogue;
+
+    #[test]
+    fn test_resolves_symbols() {
+        let catalogue = catalogue();
+        assert_eq!(catalogue.resolve("BTCCAD").unwrap(), 1);
+        assert_eq!(catalogue.resolve("usdccad").unwrap(), 90);
+        assert!(matches!(
+            catalogue.resolve("ETHCAD"),
+            Err(NdaxError::UnknownInstrument(symbol)) if symbol == "ETHCAD"
+        ));
+        assert_eq!(catalogue.len(), 2);
+    }
+
+    #[test]
+    fn test_supplies_increments() {
+        let catalogue = catalogue();
+        let increments = catalogue.increments(90).unwrap();
+        assert!(increments.is_valid_price(rust_decimal::Decimal::new(13602, 4)));
+        assert!(catalogue.increments(2).is_err());
+    }
+}
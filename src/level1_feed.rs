@@ -0,0 +1,191 @@
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+use crate::constants;
+use crate::entities::level1::Level1;
+use crate::error::NdaxError;
+use crate::frame::Frame;
+use crate::ws_client::WsClient;
+
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+/// Best bid/offer and session statistics for subscribed instruments.
+///
+/// A background task decodes SubscribeLevel1 replies and Level1UpdateEvent
+/// frames from the client, keeps the latest ticker per instrument and
+/// publishes every update to `updates()` receivers. Subscriptions are replayed
+/// by the client after a reconnect, so the feed resumes on its own.
+#[derive(Clone)]
+pub struct Level1Feed {
+    client: WsClient,
+    updates: broadcast::Sender<Level1>,
+    tickers: Arc<RwLock<Tickers>>,
+}
+
+// Kept behind one lock so an update racing an unsubscribe cannot bring the
+// instrument's ticker back
+#[derive(Debug, Default)]
+struct Tickers {
+    subscribed: HashSet<u64>,
+    latest: HashMap<u64, Level1>,
+}
+
+impl Level1Feed {
+    pub fn new(client: WsClient) -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        let feed = Level1Feed {
+            client,
+            updates,
+            tickers: Arc::new(RwLock::new(Tickers::default())),
+        };
+
+        let mut events = feed.client.events();
+        let updates = feed.updates.clone();
+        let tickers = Arc::clone(&feed.tickers);
+        tokio::spawn(async move {
+            loop {
+                let frame = match events.recv().await {
+                    Ok(frame) => frame,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        eprintln!("Level1 feed skipped {} frames", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                match decode(&frame) {
+                    Some(Ok(level1)) => publish(&tickers, &updates, level1),
+                    Some(Err(e)) => eprintln!("Failed to parse {}: {}", frame.function_name, e),
+                    None => (),
+                }
+            }
+        });
+        feed
+    }
+
+    // Subscribes to an instrument's ticker and returns its current value
+    pub async fn subscribe(&self, instrument_id: u64) -> Result<Level1, NdaxError> {
+        let payload = json!({ "OMSId": 1, "InstrumentId": instrument_id });
+        // Marked first, so updates arriving before the reply is handled are kept
+        self.tickers
+            .write()
+            .unwrap()
+            .subscribed
+            .insert(instrument_id);
+        let reply = match self
+            .client
+            .subscribe(constants::SUBSCRIBE_LEVEL1, payload)
+            .await
+        {
+            Ok(reply) => reply,
+            Err(e) => {
+                self.tickers.write().unwrap().forget(instrument_id);
+                return Err(e);
+            }
+        };
+        let level1: Level1 = serde_json::from_value(reply)?;
+        publish(&self.tickers, &self.updates, level1.clone());
+        Ok(level1)
+    }
+
+    pub async fn unsubscribe(&self, instrument_id: u64) -> Result<(), NdaxError> {
+        let payload = json!({ "OMSId": 1, "InstrumentId": instrument_id });
+        // Forgotten first, so updates still in flight are dropped
+        self.tickers.write().unwrap().forget(instrument_id);
+        self.client
+            .unsubscribe(constants::UNSUBSCRIBE_LEVEL1, payload)
+            .await?;
+        Ok(())
+    }
+
+    // A new receiver of every ticker update from this point on
+    pub fn updates(&self) -> broadcast::Receiver<Level1> {
+        self.updates.subscribe()
+    }
+
+    // The last ticker received for an instrument
+    pub fn latest(&self, instrument_id: u64) -> Option<Level1> {
+        self.tickers
+            .read()
+            .unwrap()
+            .latest
+            .get(&instrument_id)
+            .cloned()
+    }
+}
+
+impl Tickers {
+    fn forget(&mut self, instrument_id: u64) {
+        self.subscribed.remove(&instrument_id);
+        self.latest.remove(&instrument_id);
+    }
+}
+
+// Decodes a Level1 frame, or returns None for any other frame
+pub fn decode(frame: &Frame) -> Option<Result<Level1, NdaxError>> {
+    match frame.function_name.as_str() {
+        constants::SUBSCRIBE_LEVEL1 | constants::UPDATE_LEVEL1 => Some(frame.payload_as()),
+        _ => None,
+    }
+}
+
+// Keeps and broadcasts a ticker, unless its instrument is no longer subscribed
+fn publish(tickers: &RwLock<Tickers>, updates: &broadcast::Sender<Level1>, level1: Level1) {
+    {
+        let mut tickers = tickers.write().unwrap();
+        if !tickers.subscribed.contains(&level1.instrument_id) {
+            return;
+        }
+        tickers.latest.insert(level1.instrument_id, level1.clone());
+    }
+    // Nobody listening is fine, the latest value is still kept
+    let _ = updates.send(level1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::MessageType;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_decodes_level1_frames() {
+        let update = Frame::new(
+            MessageType::Event,
+            0,
+            "Level1UpdateEvent",
+            json!({"OMSId":1,"InstrumentId":90,"BestBid":1.3599,"BestOffer":1.3602,
+                   "LastTradedPx":1.36,"Volume":1500,"SessionHigh":1.3650,
+                   "SessionLow":1.3550,"TimeStamp":1718007168600u64}),
+        );
+        let level1 = decode(&update).unwrap().unwrap();
+        assert_eq!(level1.instrument_id, 90);
+        assert_eq!(level1.best_offer, Decimal::new(13602, 4));
+        assert_eq!(level1.session_high, Decimal::new(1365, 3));
+
+        let trades = Frame::new(MessageType::Event, 0, "TradeDataUpdateEvent", json!([]));
+        assert!(decode(&trades).is_none());
+    }
+
+    #[test]
+    fn test_publish_keeps_latest() {
+        let tickers = RwLock::new(Tickers::default());
+        tickers.write().unwrap().subscribed.insert(1);
+        let (updates, mut receiver) = broadcast::channel(4);
+        let level1: Level1 = serde_json::from_value(json!({
+            "InstrumentId": 1, "BestBid": 5711.7, "BestOffer": 5711.8, "LastTradedPx": 5711.7
+        }))
+        .unwrap();
+
+        publish(&tickers, &updates, level1.clone());
+        assert_eq!(tickers.read().unwrap().latest.get(&1), Some(&level1));
+        assert_eq!(receiver.try_recv().unwrap(), level1);
+
+        // An update arriving after unsubscribe is dropped rather than kept
+        tickers.write().unwrap().forget(1);
+        publish(&tickers, &updates, level1);
+        assert!(tickers.read().unwrap().latest.is_empty());
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//! Client library for the NDAX exchange.
+//!
+//! - [`ws_client::WsClient`] talks to the WebSocket gateway, using the typed
+//!   [`frame::Frame`] codec, and keeps the connection alive across drops.
+//! - [`order_book::OrderBook`] and [`order_book_registry::OrderBookRegistry`]
+//!   maintain Level2 books from snapshots and update events;
+//!   [`level1_feed::Level1Feed`] tracks best bid/offer only.
+//! - [`auth::AuthSession`] signs in once and shares the session token
+//!   between REST calls; it also signs WebSocket connections in.
+//! - [`order_manager::OrderManager`] and [`exchange_manager::ExchangeManager`]
+//!   wrap the private and public REST endpoints.
+//! - [`account_session::AccountSession`] signs in over the gateway and
+//!   delivers typed account events.
+//! - [`order_tracker::OrderTracker`] follows each order's lifecycle from
+//!   account events, and [`portfolio::Portfolio`] does the same for balances.
+//! - [`instrument_catalogue::InstrumentCatalogue`] resolves instrument symbols
+//!   and supplies the increments orders are validated against.
+//! - [`recorder::MarketDataRecorder`] persists Level2 snapshots and deltas
+//!   per instrument for offline research, and [`replay::Replay`] plays
+//!   recorded data back as gateway frames.
+//! - [`trade_sink::TradeSink`] archives public trades per instrument and UTC day,
+//!   and [`parquet_export`] exports trades and Level2 rows as CSV or Parquet.
+//! - [`store::Store`] keeps trades, tracked orders, fills and positions in SQLite.
+//! - [`entities`] holds the typed messages exchanged with NDAX.
+//!
+//! Every fallible call returns [`error::NdaxError`].
+
+pub mod account_session;
+pub mod auth;
+pub mod constants;
+pub mod csv_export;
+pub mod entities;
+pub mod error;
+pub mod exchange_manager;
+pub mod frame;
+pub mod increments;
+pub mod instrument_catalogue;
+pub mod level1_feed;
+pub mod order_book;
+pub mod order_book_registry;
+pub mod order_manager;
+pub mod order_tracker;
+pub mod parquet_export;
+pub mod portfolio;
+pub mod recorder;
+pub mod replay;
+mod rest;
+pub mod store;
+#[cfg(test)]
+mod test_support;
+pub mod trade_sink;
+pub mod utc;
+pub mod ws_client;
@@ -1,17 +1,57 @@
-use serde_json::Value;
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
 use std::fmt;
 
+use crate::constants;
+use crate::entities::level2_entry::{ActionType, Level2Entry, Side};
+use crate::error::NdaxError;
+use crate::frame::Frame;
+use crate::ws_client::WsClient;
+
+// Deltas kept while waiting for a snapshot, beyond which the oldest are dropped
+const MAX_BUFFERED_UPDATES: usize = 10_000;
+
+/// Aggregated volume resting at one price.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Level {
-    price: f64,
-    volume: f64,
+    price: Decimal,
+    volume: Decimal,
+}
+
+impl Level {
+    pub fn price(&self) -> Decimal {
+        self.price
+    }
+
+    pub fn volume(&self) -> Decimal {
+        self.volume
+    }
+}
+
+/// Whether the book can be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookState {
+    // Every delta since the last snapshot has been applied in order
+    Synced,
+    // A gap was detected, deltas are buffered until a fresh snapshot is loaded
+    Resyncing,
+    // No snapshot yet, or the feed was lost; the book must not be quoted on
+    Stale,
 }
 
+/// Level2 book for one instrument, truncated to a fixed depth on each side.
 #[derive(Debug, Clone)]
 pub struct OrderBook {
     depth: usize,
     bids: Vec<Level>,
     asks: Vec<Level>,
+    state: BookState,
+    // MDUpdateId of the last delta applied, None until the first delta after a snapshot
+    last_update_id: Option<u64>,
+    snapshot_update_id: u64,
+    buffered: VecDeque<Level2Entry>,
 }
 
 impl OrderBook {
@@ -20,101 +60,174 @@ impl OrderBook {
             depth,
             bids: Vec::with_capacity(depth),
             asks: Vec::with_capacity(depth),
+            state: BookState::Stale,
+            last_update_id: None,
+            snapshot_update_id: 0,
+            buffered: VecDeque::new(),
         }
     }
 
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    // Bid levels, best first
+    pub fn bids(&self) -> &[Level] {
+        &self.bids
+    }
+
+    // Ask levels, best first
+    pub fn asks(&self) -> &[Level] {
+        &self.asks
+    }
+
+    pub fn best_bid(&self) -> Option<&Level> {
+        self.bids.first()
+    }
+
+    pub fn best_ask(&self) -> Option<&Level> {
+        self.asks.first()
+    }
+
+    pub fn state(&self) -> BookState {
+        self.state
+    }
+
+    pub fn last_update_id(&self) -> Option<u64> {
+        self.last_update_id
+    }
+
+    // Flags the book as untrustworthy, e.g. when the connection feeding it drops
+    pub fn mark_stale(&mut self) {
+        self.state = BookState::Stale;
+    }
+
     // Initializes the order book with a snapshot
-    pub fn initialize(&mut self, snapshot: &Value) {
-        if let Some(order_string) = snapshot.get("o").and_then(Value::as_str) {
-            if let Ok(orders) = serde_json::from_str::<Vec<Vec<Value>>>(order_string) {
-                println!("Orders: {:?}", orders);
-                for order in orders.iter() {
-                    if let Some(order_type) = order.last().and_then(|v| v.as_i64()) {
-                        if order_type == 0 {
-                            // This is a bid
-                            if let (Some(price), Some(volume)) = (
-                                order.get(6).and_then(|v| v.as_f64()),
-                                order.get(8).and_then(|v| v.as_f64()),
-                            ) {
-                                self.bids.push(Level { price, volume });
-                            }
-                        } else if order_type == 1 {
-                            // This is an ask
-                            if let (Some(price), Some(volume)) = (
-                                order.get(6).and_then(|v| v.as_f64()),
-                                order.get(8).and_then(|v| v.as_f64()),
-                            ) {
-                                self.asks.push(Level { price, volume });
-                            }
-                        } else {
-                            println!("Failed to parse 'o' into an array");
-                        }
-                    } else {
-                        println!("'o' is not a string");
-                    }
-                }
-
-                // Sort bids and asks
-                self.bids
-                    .sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap());
-                self.asks
-                    .sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+    pub fn initialize(&mut self, snapshot: &Frame) {
+        match snapshot.payload_as::<Vec<Level2Entry>>() {
+            Ok(entries) => self.apply_snapshot(entries),
+            Err(e) => println!("Failed to parse snapshot: {}", e),
+        }
+    }
+
+    // Fetches a fresh GetL2Snapshot and replays the deltas buffered since the gap
+    pub async fn resync(&mut self, client: &WsClient, instrument_id: u64) -> Result<(), NdaxError> {
+        self.state = BookState::Resyncing;
+        match fetch_snapshot(client, instrument_id, self.depth).await {
+            Ok(entries) => {
+                self.apply_snapshot(entries);
+                Ok(())
+            }
+            Err(e) => {
+                self.mark_stale();
+                Err(e)
             }
         }
     }
 
-    pub fn update(&mut self, update: &serde_json::Value) {
-        if let Some(update_string) = update.get("o").and_then(Value::as_str) {
-            if let Ok(update_data) = serde_json::from_str::<Vec<Vec<Value>>>(update_string) {
-                for order in update_data {
-                    if let Some(order_type) = order.last().and_then(|v| v.as_i64()) {
-                        let price = order.get(6).and_then(|v| v.as_f64()).unwrap_or(0.0);
-                        let volume = order.get(8).and_then(|v| v.as_f64()).unwrap_or(0.0);
-
-                        if order_type == 0 {
-                            // This is a bid
-                            if volume == 0.0 {
-                                // Delete the price level with 0 volume
-                                self.bids.retain(|b| b.price != price);
-                            } else {
-                                // Check if the price level exists and update or insert accordingly
-                                match self.bids.iter_mut().find(|b| b.price == price) {
-                                    Some(existing_bid) => existing_bid.volume = volume, // Update existing
-                                    None => {
-                                        // Insert new price level in sorted order
-                                        let new_bid = Level { price, volume };
-                                        self.bids.push(new_bid);
-                                        self.bids
-                                            .sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap());
-                                    }
-                                }
-                            }
-                        } else if order_type == 1 {
-                            // This is an ask
-                            if volume == 0.0 {
-                                // Delete the price level with 0 volume
-                                self.asks.retain(|a| a.price != price);
-                            } else {
-                                // Check if the price level exists and update or insert accordingly
-                                match self.asks.iter_mut().find(|a| a.price == price) {
-                                    Some(existing_ask) => existing_ask.volume = volume, // Update existing
-                                    None => {
-                                        // Insert new price level in sorted order
-                                        let new_ask = Level { price, volume };
-                                        self.asks.push(new_ask);
-                                        self.asks
-                                            .sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    // Replaces the book's levels with a snapshot
+    pub fn apply_snapshot(&mut self, entries: Vec<Level2Entry>) {
+        self.bids.clear();
+        self.asks.clear();
+        for entry in entries.iter() {
+            let level = Level {
+                price: entry.price,
+                volume: entry.quantity,
+            };
+            match entry.side {
+                Side::Buy => self.bids.push(level),
+                Side::Sell => self.asks.push(level),
             }
         }
+
+        // Sort bids and asks
+        sort_levels(&mut self.bids, Side::Buy);
+        sort_levels(&mut self.asks, Side::Sell);
+
+        // Levels removed since their last change are absent from a snapshot, so its
+        // newest id is only a lower bound; the next delta re-establishes the sequence
+        self.snapshot_update_id = entries.iter().map(|e| e.md_update_id).max().unwrap_or(0);
+        self.last_update_id = None;
+        self.state = BookState::Synced;
+
+        let buffered = std::mem::take(&mut self.buffered);
+        for entry in buffered {
+            self.apply(entry);
+        }
+        self.truncate_to_depth();
+    }
+
+    pub fn update(&mut self, update: &Frame) {
+        match update.payload_as::<Vec<Level2Entry>>() {
+            Ok(entries) => self.apply_updates(entries),
+            Err(e) => println!("Failed to parse update: {}", e),
+        }
+    }
+
+    // Applies Level2UpdateEvent rows in order
+    pub fn apply_updates(&mut self, entries: Vec<Level2Entry>) {
+        for entry in entries {
+            self.apply(entry);
+        }
         self.truncate_to_depth();
     }
 
+    // Applies a delta in sequence, or buffers it while the book is out of sync
+    fn apply(&mut self, entry: Level2Entry) {
+        if self.state != BookState::Synced {
+            if self.buffered.len() == MAX_BUFFERED_UPDATES {
+                self.buffered.pop_front();
+            }
+            self.buffered.push_back(entry);
+            return;
+        }
+
+        let id = entry.md_update_id;
+        let expected = match self.last_update_id {
+            Some(last) => last + 1,
+            None => self.snapshot_update_id + 1,
+        };
+        if id < expected {
+            // Already reflected in the book, e.g. a delta older than the snapshot
+            return;
+        }
+        // The first delta after a snapshot may skip ahead, see apply_snapshot
+        if self.last_update_id.is_some() && id > expected {
+            println!("Gap in updates: expected {}, got {}", expected, id);
+            self.state = BookState::Resyncing;
+            self.buffered.push_back(entry);
+            return;
+        }
+        self.last_update_id = Some(id);
+        self.apply_level(&entry);
+    }
+
+    fn apply_level(&mut self, entry: &Level2Entry) {
+        let price = entry.price;
+        let volume = entry.quantity;
+        let levels = match entry.side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+
+        if entry.action_type == ActionType::Delete || volume.is_zero() {
+            // Delete the price level
+            levels.retain(|l| l.price != price);
+            return;
+        }
+
+        // New and Update are both applied as an upsert, since an updated level may
+        // have been truncated out of the book
+        match levels.iter_mut().find(|l| l.price == price) {
+            Some(existing) => existing.volume = volume, // Update existing
+            None => {
+                // Insert new price level in sorted order
+                levels.push(Level { price, volume });
+                sort_levels(levels, entry.side);
+            }
+        }
+    }
+
     fn truncate_to_depth(&mut self) {
         // Truncate asks to the specified depth
         if self.asks.len() > self.depth {
@@ -126,10 +239,27 @@ impl OrderBook {
         }
 
         // Since we may have inserted a new price level, ensure the order book is sorted
-        self.asks
-            .sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
-        self.bids
-            .sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap());
+        sort_levels(&mut self.asks, Side::Sell);
+        sort_levels(&mut self.bids, Side::Buy);
+    }
+}
+
+// Requests a GetL2Snapshot for one instrument over the gateway
+pub async fn fetch_snapshot(
+    client: &WsClient,
+    instrument_id: u64,
+    depth: usize,
+) -> Result<Vec<Level2Entry>, NdaxError> {
+    let payload = json!({"OMSId": 1, "InstrumentId": instrument_id, "Depth": depth});
+    let snapshot = client.call(constants::GET_L2_SNAPSHOT, payload).await?;
+    Ok(serde_json::from_value(snapshot)?)
+}
+
+// Bids are kept best (highest) first, asks best (lowest) first
+fn sort_levels(levels: &mut [Level], side: Side) {
+    match side {
+        Side::Buy => levels.sort_by_key(|l| Reverse(l.price)),
+        Side::Sell => levels.sort_by_key(|l| l.price),
     }
 }
 
@@ -144,8 +274,8 @@ impl fmt::Display for OrderBook {
         writeln!(f, "Order Book:")?;
         writeln!(
             f,
-            "           {:<12} {:<11} | {:<12 } {}",
-            "Bid", "Depth", "Ask", "Depth"
+            "           {:<12} {:<11} | {:<12 } Depth",
+            "Bid", "Depth", "Ask"
         )?;
         for i in 0..self.depth {
             let bid_level = self
@@ -174,147 +304,151 @@ mod tests {
     use super::*;
     use serde_json::Value;
 
-    fn get_snapshot() -> Value {
-        serde_json::json!({
+    fn to_frame(value: Value) -> Frame {
+        Frame::decode(&value.to_string()).unwrap()
+    }
+
+    fn get_snapshot() -> Frame {
+        to_frame(serde_json::json!({
             "i": 1,
             "m": 1,
             "n": "SubscribeLevel2",
-            "o": "[[1,1718003785385,0,5711.80000,1,0,1,8.13439401,1],
-                  [2,1718003785385,0,5712.20000,1,0,1,2.00000000,1],
-                  [3,1718003785385,0,5712.80000,1,0,1,0.30000000,1],
-                  [4,1718003785385,0,5713.00000,1,0,1,3.29800000,1],
-                  [5,1718003785385,0,5713.10000,1,0,1,1.00000000,1],
-                  [6,1718003785385,0,5713.90000,1,0,1,1.00000000,1],
-                  [7,1718003785385,0,5714.70000,1,0,1,0.50000000,1],
-                  [8,1718003785385,0,5715.20000,1,0,1,1.00000000,1],
-                  [9,1718003785385,0,5716.60000,1,0,1,1.22700000,1],
-                  [10,1718003785385,0,5716.80000,1,0,1,0.35000000,1],
-                  [11,1718003785385,0,5711.70000,1,0,0,0.00749800,0],
-                  [12,1718003785385,0,5709.20000,1,0,0,3.30000000,0],
-                  [13,1718003785385,0,5708.30000,1,0,0,0.75483907,0],
-                  [14,1718003785385,0,5708.20000,1,0,0,5.00000000,0],
-                  [15,1718003785385,0,5707.80000,1,0,0,2.50000000,0],
-                  [16,1718003785385,0,5707.40000,1,0,0,4.33000000,0],
-                  [17,1718003785385,0,5707.00000,1,0,0,0.00200000,0],
-                  [18,1718003785385,0,5706.90000,1,0,0,1.17300000,0],
-                  [19,1718003785385,0,5706.40000,1,0,0,0.85600000,0],
-                  [20,1718003785385,0,5706.30000,1,0,0,1.00000000,0]]"
-        })
-    }
-
-    fn get_update1() -> Value {
-        serde_json::json!({
+            "o": "[[1,1,1718003785385,0,5711.70000,1,5711.80000,1,8.13439401,1],
+                  [2,1,1718003785385,0,5711.70000,1,5712.20000,1,2.00000000,1],
+                  [3,1,1718003785385,0,5711.70000,1,5712.80000,1,0.30000000,1],
+                  [4,1,1718003785385,0,5711.70000,1,5713.00000,1,3.29800000,1],
+                  [5,1,1718003785385,0,5711.70000,1,5713.10000,1,1.00000000,1],
+                  [6,1,1718003785385,0,5711.70000,1,5713.90000,1,1.00000000,1],
+                  [7,1,1718003785385,0,5711.70000,1,5714.70000,1,0.50000000,1],
+                  [8,1,1718003785385,0,5711.70000,1,5715.20000,1,1.00000000,1],
+                  [9,1,1718003785385,0,5711.70000,1,5716.60000,1,1.22700000,1],
+                  [10,1,1718003785385,0,5711.70000,1,5716.80000,1,0.35000000,1],
+                  [11,1,1718003785385,0,5711.70000,1,5711.70000,1,0.00749800,0],
+                  [12,1,1718003785385,0,5711.70000,1,5709.20000,1,3.30000000,0],
+                  [13,1,1718003785385,0,5711.70000,1,5708.30000,1,0.75483907,0],
+                  [14,1,1718003785385,0,5711.70000,1,5708.20000,1,5.00000000,0],
+                  [15,1,1718003785385,0,5711.70000,1,5707.80000,1,2.50000000,0],
+                  [16,1,1718003785385,0,5711.70000,1,5707.40000,1,4.33000000,0],
+                  [17,1,1718003785385,0,5711.70000,1,5707.00000,1,0.00200000,0],
+                  [18,1,1718003785385,0,5711.70000,1,5706.90000,1,1.17300000,0],
+                  [19,1,1718003785385,0,5711.70000,1,5706.40000,1,0.85600000,0],
+                  [20,1,1718003785385,0,5711.70000,1,5706.30000,1,1.00000000,0]]"
+        }))
+    }
+
+    fn get_update1() -> Frame {
+        to_frame(serde_json::json!({
             "i": 140,
             "m": 3,
             "n": "Level2UpdateEvent",
-            "o": "[[261,1718007168597,2,5709.20000,0,0,1,3.00000000,0],
-                  [262,1718007168597,1,5708.20000,2,0,1,0.00000000,0],
-                  [263,1718007169610,0,5705.90000,1,0,1,7.62400000,0]]"
-        })
+            "o": "[[261,1,1718007168597,1,5711.70000,1,5709.20000,1,3.00000000,0],
+                  [262,0,1718007168597,2,5711.70000,0,5708.20000,1,0.00000000,0],
+                  [263,1,1718007169610,0,5711.70000,1,5705.90000,1,7.62400000,0]]"
+        }))
     }
 
-    fn get_update2() -> Value {
-        serde_json::json!({
+    fn get_update2() -> Frame {
+        to_frame(serde_json::json!({
             "i": 141,
             "m": 3,
             "n": "Level2UpdateEvent",
-            "o": "[[264,1718007169611,2,5709.20000,0,0,1,8.00000000,0],
-                  [265,1718007169612,1,5709.40000,0,0,1,0.30000000,0]]"
-        })
+            "o": "[[264,1,1718007169611,1,5711.70000,1,5709.20000,1,8.00000000,0],
+                  [265,1,1718007169612,0,5711.70000,1,5709.40000,1,0.30000000,0]]"
+        }))
     }
 
-    fn get_update3() -> Value {
-        serde_json::json!({
+    fn get_update3() -> Frame {
+        to_frame(serde_json::json!({
             "i": 142,
             "m": 3,
             "n": "Level2UpdateEvent",
-            "o": "[[266,1718007169613,1,5708.30000,0,0,1,0.00000000,0],
-                  [267,1718007169614,2,5705.90000,0,0,1,7.62400000,0]]"
-        })
+            "o": "[[266,0,1718007169613,2,5711.70000,0,5708.30000,1,0.00000000,0],
+                  [267,1,1718007169614,1,5711.70000,1,5705.90000,1,7.62400000,0]]"
+        }))
     }
 
-    fn get_expected_order_book1() -> Value {
-        serde_json::json!({
+    fn get_expected_order_book1() -> Frame {
+        to_frame(serde_json::json!({
             "i": 1,
             "m": 1,
             "n": "SubscribeLevel2",
-            "o": "[[1,1718003785385,0,5711.80000,1,0,1,8.13439401,1],
-                  [2,1718003785385,0,5712.20000,1,0,1,2.00000000,1],
-                  [3,1718003785385,0,5712.80000,1,0,1,0.30000000,1],
-                  [4,1718003785385,0,5713.00000,1,0,1,3.29800000,1],
-                  [5,1718003785385,0,5713.10000,1,0,1,1.00000000,1],
-                  [6,1718003785385,0,5713.90000,1,0,1,1.00000000,1],
-                  [7,1718003785385,0,5714.70000,1,0,1,0.50000000,1],
-                  [8,1718003785385,0,5715.20000,1,0,1,1.00000000,1],
-                  [9,1718003785385,0,5716.60000,1,0,1,1.22700000,1],
-                  [10,1718003785385,0,5716.80000,1,0,1,0.35000000,1],
-                  [11,1718003785385,0,5711.70000,1,0,0,0.00749800,0],
-                  [12,1718003785385,0,5709.20000,1,0,0,3.00000000,0],
-                  [13,1718003785385,0,5708.30000,1,0,0,0.75483907,0],
-                  [14,1718003785385,0,5707.80000,1,0,0,2.50000000,0],
-                  [15,1718003785385,0,5707.40000,1,0,0,4.33000000,0],
-                  [16,1718003785385,0,5707.00000,1,0,0,0.00200000,0],
-                  [17,1718003785385,0,5706.90000,1,0,0,1.17300000,0],
-                  [18,1718003785385,0,5706.40000,1,0,0,0.85600000,0],
-                  [19,1718003785385,0,5706.30000,1,0,0,1.00000000,0],
-                  [20,1718003785385,0,5705.90000,1,0,0,7.62400000,0]]"
-        })
-    }
-
-    fn get_expected_order_book2() -> Value {
-        serde_json::json!({
+            "o": "[[1,1,1718003785385,0,5711.70000,1,5711.80000,1,8.13439401,1],
+                  [2,1,1718003785385,0,5711.70000,1,5712.20000,1,2.00000000,1],
+                  [3,1,1718003785385,0,5711.70000,1,5712.80000,1,0.30000000,1],
+                  [4,1,1718003785385,0,5711.70000,1,5713.00000,1,3.29800000,1],
+                  [5,1,1718003785385,0,5711.70000,1,5713.10000,1,1.00000000,1],
+                  [6,1,1718003785385,0,5711.70000,1,5713.90000,1,1.00000000,1],
+                  [7,1,1718003785385,0,5711.70000,1,5714.70000,1,0.50000000,1],
+                  [8,1,1718003785385,0,5711.70000,1,5715.20000,1,1.00000000,1],
+                  [9,1,1718003785385,0,5711.70000,1,5716.60000,1,1.22700000,1],
+                  [10,1,1718003785385,0,5711.70000,1,5716.80000,1,0.35000000,1],
+                  [11,1,1718003785385,0,5711.70000,1,5711.70000,1,0.00749800,0],
+                  [12,1,1718003785385,0,5711.70000,1,5709.20000,1,3.00000000,0],
+                  [13,1,1718003785385,0,5711.70000,1,5708.30000,1,0.75483907,0],
+                  [14,1,1718003785385,0,5711.70000,1,5707.80000,1,2.50000000,0],
+                  [15,1,1718003785385,0,5711.70000,1,5707.40000,1,4.33000000,0],
+                  [16,1,1718003785385,0,5711.70000,1,5707.00000,1,0.00200000,0],
+                  [17,1,1718003785385,0,5711.70000,1,5706.90000,1,1.17300000,0],
+                  [18,1,1718003785385,0,5711.70000,1,5706.40000,1,0.85600000,0],
+                  [19,1,1718003785385,0,5711.70000,1,5706.30000,1,1.00000000,0],
+                  [20,1,1718003785385,0,5711.70000,1,5705.90000,1,7.62400000,0]]"
+        }))
+    }
+
+    fn get_expected_order_book2() -> Frame {
+        to_frame(serde_json::json!({
             "i": 2,
             "m": 1,
             "n": "SubscribeLevel2",
-            "o": "[[1,1718003785385,0,5711.80000,1,0,1,8.13439401,1],
-                  [2,1718003785385,0,5712.20000,1,0,1,2.00000000,1],
-                  [3,1718003785385,0,5712.80000,1,0,1,0.30000000,1],
-                  [4,1718003785385,0,5713.00000,1,0,1,3.29800000,1],
-                  [5,1718003785385,0,5713.10000,1,0,1,1.00000000,1],
-                  [6,1718003785385,0,5713.90000,1,0,1,1.00000000,1],
-                  [7,1718003785385,0,5714.70000,1,0,1,0.50000000,1],
-                  [8,1718003785385,0,5715.20000,1,0,1,1.00000000,1],
-                  [9,1718003785385,0,5716.60000,1,0,1,1.22700000,1],
-                  [10,1718003785385,0,5716.80000,1,0,1,0.35000000,1],
-                  [11,1718003785385,0,5711.70000,1,0,0,0.00749800,0],
-                  [12,1718003785385,0,5709.40000,1,0,0,0.30000000,0],
-                  [13,1718003785385,0,5709.20000,1,0,0,8.00000000,0],
-                  [14,1718003785385,0,5708.30000,1,0,0,0.75483907,0],
-                  [15,1718003785385,0,5707.80000,1,0,0,2.50000000,0],
-                  [16,1718003785385,0,5707.40000,1,0,0,4.33000000,0],
-                  [17,1718003785385,0,5707.00000,1,0,0,0.00200000,0],
-                  [18,1718003785385,0,5706.90000,1,0,0,1.17300000,0],
-                  [19,1718003785385,0,5706.40000,1,0,0,0.85600000,0],
-                  [20,1718003785385,0,5706.30000,1,0,0,1.00000000,0]]"
-        })
-    }
-
-    fn get_expected_order_book3() -> Value {
-        serde_json::json!({
+            "o": "[[1,1,1718003785385,0,5711.70000,1,5711.80000,1,8.13439401,1],
+                  [2,1,1718003785385,0,5711.70000,1,5712.20000,1,2.00000000,1],
+                  [3,1,1718003785385,0,5711.70000,1,5712.80000,1,0.30000000,1],
+                  [4,1,1718003785385,0,5711.70000,1,5713.00000,1,3.29800000,1],
+                  [5,1,1718003785385,0,5711.70000,1,5713.10000,1,1.00000000,1],
+                  [6,1,1718003785385,0,5711.70000,1,5713.90000,1,1.00000000,1],
+                  [7,1,1718003785385,0,5711.70000,1,5714.70000,1,0.50000000,1],
+                  [8,1,1718003785385,0,5711.70000,1,5715.20000,1,1.00000000,1],
+                  [9,1,1718003785385,0,5711.70000,1,5716.60000,1,1.22700000,1],
+                  [10,1,1718003785385,0,5711.70000,1,5716.80000,1,0.35000000,1],
+                  [11,1,1718003785385,0,5711.70000,1,5711.70000,1,0.00749800,0],
+                  [12,1,1718003785385,0,5711.70000,1,5709.40000,1,0.30000000,0],
+                  [13,1,1718003785385,0,5711.70000,1,5709.20000,1,8.00000000,0],
+                  [14,1,1718003785385,0,5711.70000,1,5708.30000,1,0.75483907,0],
+                  [15,1,1718003785385,0,5711.70000,1,5707.80000,1,2.50000000,0],
+                  [16,1,1718003785385,0,5711.70000,1,5707.40000,1,4.33000000,0],
+                  [17,1,1718003785385,0,5711.70000,1,5707.00000,1,0.00200000,0],
+                  [18,1,1718003785385,0,5711.70000,1,5706.90000,1,1.17300000,0],
+                  [19,1,1718003785385,0,5711.70000,1,5706.40000,1,0.85600000,0],
+                  [20,1,1718003785385,0,5711.70000,1,5706.30000,1,1.00000000,0]]"
+        }))
+    }
+
+    fn get_expected_order_book3() -> Frame {
+        to_frame(serde_json::json!({
             "i": 3,
             "m": 1,
             "n": "SubscribeLevel2",
-            "o": "[[1,1718003785385,0,5711.80000,1,0,1,8.13439401,1],
-                  [2,1718003785385,0,5712.20000,1,0,1,2.00000000,1],
-                  [3,1718003785385,0,5712.80000,1,0,1,0.30000000,1],
-                  [4,1718003785385,0,5713.00000,1,0,1,3.29800000,1],
-                  [5,1718003785385,0,5713.10000,1,0,1,1.00000000,1],
-                  [6,1718003785385,0,5713.90000,1,0,1,1.00000000,1],
-                  [7,1718003785385,0,5714.70000,1,0,1,0.50000000,1],
-                  [8,1718003785385,0,5715.20000,1,0,1,1.00000000,1],
-                  [9,1718003785385,0,5716.60000,1,0,1,1.22700000,1],
-                  [10,1718003785385,0,5716.80000,1,0,1,0.35000000,1],
-                  [11,1718003785385,0,5711.70000,1,0,0,0.00749800,0],
-                  [12,1718003785385,0,5709.40000,1,0,0,0.30000000,0],
-                  [13,1718003785385,0,5709.20000,1,0,0,8.00000000,0],
-                  [14,1718003785385,0,5707.80000,1,0,0,2.50000000,0],
-                  [15,1718003785385,0,5707.40000,1,0,0,4.33000000,0],
-                  [16,1718003785385,0,5707.00000,1,0,0,0.00200000,0],
-                  [17,1718003785385,0,5706.90000,1,0,0,1.17300000,0],
-                  [18,1718003785385,0,5706.40000,1,0,0,0.85600000,0],
-                  [19,1718003785385,0,5706.30000,1,0,0,1.00000000,0],
-                  [20,1718003785385,0,5705.90000,1,0,0,7.62400000,0]]"
-        })
+            "o": "[[1,1,1718003785385,0,5711.70000,1,5711.80000,1,8.13439401,1],
+                  [2,1,1718003785385,0,5711.70000,1,5712.20000,1,2.00000000,1],
+                  [3,1,1718003785385,0,5711.70000,1,5712.80000,1,0.30000000,1],
+                  [4,1,1718003785385,0,5711.70000,1,5713.00000,1,3.29800000,1],
+                  [5,1,1718003785385,0,5711.70000,1,5713.10000,1,1.00000000,1],
+                  [6,1,1718003785385,0,5711.70000,1,5713.90000,1,1.00000000,1],
+                  [7,1,1718003785385,0,5711.70000,1,5714.70000,1,0.50000000,1],
+                  [8,1,1718003785385,0,5711.70000,1,5715.20000,1,1.00000000,1],
+                  [9,1,1718003785385,0,5711.70000,1,5716.60000,1,1.22700000,1],
+                  [10,1,1718003785385,0,5711.70000,1,5716.80000,1,0.35000000,1],
+                  [11,1,1718003785385,0,5711.70000,1,5711.70000,1,0.00749800,0],
+                  [12,1,1718003785385,0,5711.70000,1,5709.40000,1,0.30000000,0],
+                  [13,1,1718003785385,0,5711.70000,1,5709.20000,1,8.00000000,0],
+                  [14,1,1718003785385,0,5711.70000,1,5707.80000,1,2.50000000,0],
+                  [15,1,1718003785385,0,5711.70000,1,5707.40000,1,4.33000000,0],
+                  [16,1,1718003785385,0,5711.70000,1,5707.00000,1,0.00200000,0],
+                  [17,1,1718003785385,0,5711.70000,1,5706.90000,1,1.17300000,0],
+                  [18,1,1718003785385,0,5711.70000,1,5706.40000,1,0.85600000,0],
+                  [19,1,1718003785385,0,5711.70000,1,5706.30000,1,1.00000000,0],
+                  [20,1,1718003785385,0,5711.70000,1,5705.90000,1,7.62400000,0]]"
+        }))
     }
 
     #[test]
@@ -366,4 +500,70 @@ mod tests {
         assert_eq!(order_book.asks, expected_order_book.asks);
         assert_eq!(order_book.bids, expected_order_book.bids);
     }
+
+    #[test]
+    fn test_order_book_gap_triggers_resync() {
+        let mut order_book = OrderBook::new(10);
+        assert_eq!(order_book.state(), BookState::Stale);
+
+        order_book.initialize(&get_snapshot());
+        assert_eq!(order_book.state(), BookState::Synced);
+        order_book.update(&get_update1());
+        assert_eq!(order_book.last_update_id(), Some(263));
+
+        // Skipping update2 leaves a gap between 263 and 266
+        let before_gap = order_book.clone();
+        order_book.update(&get_update3());
+        assert_eq!(order_book.state(), BookState::Resyncing);
+        assert_eq!(order_book.asks, before_gap.asks);
+        assert_eq!(order_book.bids, before_gap.bids);
+
+        // A fresh snapshot taken after update2 replays the buffered update3
+        order_book.initialize(&get_expected_order_book2());
+        assert_eq!(order_book.state(), BookState::Synced);
+        assert_eq!(order_book.last_update_id(), Some(267));
+
+        let mut expected_order_book = OrderBook::new(10);
+        expected_order_book.initialize(&get_expected_order_book3());
+        assert_eq!(order_book.asks, expected_order_book.asks);
+        assert_eq!(order_book.bids, expected_order_book.bids);
+    }
+
+    #[test]
+    fn test_order_book_first_delta_may_skip_ahead_of_snapshot() {
+        let mut order_book = OrderBook::new(10);
+        order_book.initialize(&get_snapshot());
+
+        // Deltas for levels absent from the snapshot used up ids in between
+        order_book.update(&get_update2());
+        assert_eq!(order_book.state(), BookState::Synced);
+        assert_eq!(order_book.last_update_id(), Some(265));
+    }
+
+    #[test]
+    fn test_order_book_ignores_replayed_updates() {
+        let mut order_book = OrderBook::new(10);
+        order_book.initialize(&get_snapshot());
+        order_book.update(&get_update1());
+        order_book.update(&get_update2());
+
+        // Receiving update1 again must neither change the book nor trigger a resync
+        let before = order_book.clone();
+        order_book.update(&get_update1());
+        assert_eq!(order_book.state(), BookState::Synced);
+        assert_eq!(order_book.last_update_id(), Some(265));
+        assert_eq!(order_book.asks, before.asks);
+        assert_eq!(order_book.bids, before.bids);
+    }
+
+    #[test]
+    fn test_order_book_buffers_updates_before_snapshot() {
+        let mut order_book = OrderBook::new(10);
+        order_book.update(&get_update1());
+        assert_eq!(order_book.state(), BookState::Stale);
+        assert_eq!(order_book.last_update_id(), None);
+
+        order_book.initialize(&get_snapshot());
+        assert_eq!(order_book.last_update_id(), Some(263));
+    }
 }
@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use crate::constants;
+use crate::entities::level2_entry::Level2Entry;
+use crate::error::NdaxError;
+use crate::frame::Frame;
+use crate::order_book::{fetch_snapshot, BookState, OrderBook};
+use crate::ws_client::WsClient;
+
+/// Order books for several instruments, keyed by InstrumentId.
+///
+/// Handles are cheap to clone and can be shared between tasks: one task feeds
+/// gateway frames in while others take read-only snapshots of any book.
+#[derive(Debug, Clone)]
+pub struct OrderBookRegistry {
+    depth: usize,
+    books: Arc<RwLock<HashMap<u64, OrderBook>>>,
+}
+
+impl OrderBookRegistry {
+    pub fn new(depth: usize) -> Self {
+        OrderBookRegistry {
+            depth,
+            books: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    // Starts tracking an instrument, returns false if it was already tracked
+    pub fn add_instrument(&self, instrument_id: u64) -> bool {
+        let mut books = self.books.write().unwrap();
+        if books.contains_key(&instrument_id) {
+            return false;
+        }
+        books.insert(instrument_id, OrderBook::new(self.depth));
+        true
+    }
+
+    pub fn remove_instrument(&self, instrument_id: u64) -> Option<OrderBook> {
+        self.books.write().unwrap().remove(&instrument_id)
+    }
+
+    pub fn instruments(&self) -> Vec<u64> {
+        let mut instruments: Vec<u64> = self.books.read().unwrap().keys().copied().collect();
+        instruments.sort_unstable();
+        instruments
+    }
+
+    // Copy of an instrument's book as of now
+    pub fn snapshot(&self, instrument_id: u64) -> Option<OrderBook> {
+        self.books.read().unwrap().get(&instrument_id).cloned()
+    }
+
+    pub fn state(&self, instrument_id: u64) -> Option<BookState> {
+        self.books
+            .read()
+            .unwrap()
+            .get(&instrument_id)
+            .map(OrderBook::state)
+    }
+
+    // Flags every book as stale, e.g. while the feed is reconnecting
+    pub fn mark_all_stale(&self) {
+        for book in self.books.write().unwrap().values_mut() {
+            book.mark_stale();
+        }
+    }
+
+    /// Routes Level2 snapshot and update frames to the books they belong to.
+    ///
+    /// Returns the instruments whose books detected a gap and need a resync.
+    pub fn handle_frame(&self, frame: &Frame) -> Vec<u64> {
+        let is_snapshot = match frame.function_name.as_str() {
+            constants::SUBSCRIBE | constants::GET_L2_SNAPSHOT => true,
+            constants::UPDATE => false,
+            _ => return Vec::new(),
+        };
+        let entries = match frame.payload_as::<Vec<Level2Entry>>() {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!("Failed to parse {}: {}", frame.function_name, e);
+                return Vec::new();
+            }
+        };
+
+        let mut resync = Vec::new();
+        let mut books = self.books.write().unwrap();
+        for (instrument_id, entries) in group_by_instrument(entries) {
+            let Some(book) = books.get_mut(&instrument_id) else {
+                continue;
+            };
+            if is_snapshot {
+                book.apply_snapshot(entries);
+            } else {
+                book.apply_updates(entries);
+            }
+            if book.state() == BookState::Resyncing {
+                resync.push(instrument_id);
+            }
+        }
+        resync
+    }
+
+    // Reloads one book from a fresh GetL2Snapshot
+    pub async fn resync(&self, client: &WsClient, instrument_id: u64) -> Result<(), NdaxError> {
+        let result = fetch_snapshot(client, instrument_id, self.depth).await;
+
+        let mut books = self.books.write().unwrap();
+        let Some(book) = books.get_mut(&instrument_id) else {
+            return Ok(());
+        };
+        match result {
+            Ok(entries) => {
+                book.apply_snapshot(entries);
+                Ok(())
+            }
+            Err(e) => {
+                book.mark_stale();
+                Err(e)
+            }
+        }
+    }
+}
+
+// Splits rows by the ProductPairCode they carry, keeping their order
+pub(crate) fn group_by_instrument(entries: Vec<Level2Entry>) -> BTreeMap<u64, Vec<Level2Entry>> {
+    let mut grouped: BTreeMap<u64, Vec<Level2Entry>> = BTreeMap::new();
+    for entry in entries {
+        grouped
+            .entry(entry.product_pair_code)
+            .or_default()
+            .push(entry);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn to_frame(value: serde_json::Value) -> Frame {
+        Frame::decode(&value.to_string()).unwrap()
+    }
+
+    fn get_snapshot() -> Frame {
+        to_frame(json!({
+            "i": 1,
+            "m": 1,
+            "n": "SubscribeLevel2",
+            "o": "[[1,1,1718003785385,0,5711.70000,1,5711.80000,1,8.13439401,1],
+                  [2,1,1718003785385,0,5711.70000,1,5711.70000,1,0.00749800,0],
+                  [3,1,1718003785385,0,1.36000000,1,1.36020000,90,1500.00000000,1],
+                  [4,1,1718003785385,0,1.36000000,1,1.35990000,90,2500.00000000,0]]"
+        }))
+    }
+
+    fn get_update() -> Frame {
+        to_frame(json!({
+            "i": 140,
+            "m": 3,
+            "n": "Level2UpdateEvent",
+            "o": "[[5,1,1718007168597,0,1.36000000,1,1.36000000,90,100.00000000,0],
+                  [6,1,1718007168597,0,5711.70000,1,5711.75000,1,1.00000000,0]]"
+        }))
+    }
+
+    #[test]
+    fn test_routes_rows_by_instrument() {
+        let registry = OrderBookRegistry::new(10);
+        registry.add_instrument(1);
+        registry.add_instrument(90);
+
+        registry.handle_frame(&get_snapshot());
+        let btc = registry.snapshot(1).unwrap();
+        let usdc = registry.snapshot(90).unwrap();
+        assert_eq!(btc.best_bid().unwrap().price(), dec("5711.7"));
+        assert_eq!(usdc.best_bid().unwrap().price(), dec("1.3599"));
+        assert_eq!(usdc.best_ask().unwrap().price(), dec("1.3602"));
+
+        assert!(registry.handle_frame(&get_update()).is_empty());
+        assert_eq!(
+            registry.snapshot(1).unwrap().best_bid().unwrap().price(),
+            dec("5711.75")
+        );
+        assert_eq!(
+            registry.snapshot(90).unwrap().best_bid().unwrap().price(),
+            dec("1.36")
+        );
+    }
+
+    #[test]
+    fn test_ignores_untracked_instruments() {
+        let registry = OrderBookRegistry::new(10);
+        registry.add_instrument(90);
+        assert!(!registry.add_instrument(90));
+
+        registry.handle_frame(&get_snapshot());
+        assert!(registry.snapshot(1).is_none());
+        assert_eq!(registry.state(90), Some(BookState::Synced));
+
+        assert!(registry.remove_instrument(90).is_some());
+        assert!(registry.instruments().is_empty());
+    }
+}
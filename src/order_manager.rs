@@ -1,31 +1,44 @@
-use hmac::NewMac;
-use hmac::{Hmac, Mac};
+use futures_util::stream::{self, Stream, TryStreamExt};
 use reqwest::header::HeaderMap;
-use reqwest::header::HeaderName;
 use reqwest::header::HeaderValue;
 use reqwest::header::CONTENT_TYPE;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use sha2::Sha256;
+use reqwest::{Client, RequestBuilder};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::error::Error;
-use std::str::FromStr;
-use std::time::SystemTime;
-use std::time::UNIX_EPOCH;
+use std::future::Future;
 
+use crate::auth::{AuthSession, Credentials};
 use crate::constants;
+use crate::entities::account_info::AccountInfo;
+use crate::entities::account_transaction::AccountTransaction;
+use crate::entities::authenticate_response::AuthenticateResponse;
+use crate::entities::cancel_replace_response::CancelReplaceResponse;
+use crate::entities::generic_response::GenericResponse;
+use crate::entities::history_query::HistoryQuery;
+use crate::entities::new_order::NewOrder;
+use crate::entities::open_order::OpenOrder;
+use crate::entities::order_trade_event::OrderTradeEvent;
+use crate::entities::order_types::{OrderId, OrderRef};
+use crate::entities::position::Position;
+use crate::entities::send_order_response::SendOrderResponse;
+use crate::error::NdaxError;
+use crate::increments::Increments;
+use crate::rest::parse_response;
 
-// Type alias for the HMAC-SHA256 algorithm
-type HmacSha256 = Hmac<Sha256>;
-
+/// Client for the private REST endpoints of one account.
+///
+/// Private calls carry the session token of the shared `AuthSession`, which
+/// signs in on first use and again whenever NDAX rejects the token.
+#[derive(Clone)]
 pub struct OrderManager {
     api_url: String,
-    api_key: String,
-    signature: String,
     user_id: String,
     account_name: String,
     account_id: String,
+    auth: AuthSession,
     client: Client,
 }
 
@@ -38,42 +51,29 @@ impl OrderManager {
         account_name: &str,
         account_id: &str,
     ) -> Self {
+        let credentials = Credentials::new(api_key, signature, user_id);
         OrderManager {
             api_url: api_url.to_string(),
-            api_key: api_key.to_string(),
-            signature: signature.to_string(),
             user_id: user_id.to_string(),
             account_name: account_name.to_string(),
             account_id: account_id.to_string(),
+            auth: AuthSession::new(api_url, credentials),
             client: Client::new(),
         }
     }
 
-    pub fn generate_auth_dict(&self) -> HashMap<&str, String> {
-        let nonce = self.generate_nonce();
-        let raw_signature = format!("{}{}{}", nonce, self.user_id, self.api_key);
-
-        let mut mac: Hmac<Sha256> = HmacSha256::new_from_slice(self.signature.as_bytes())
-            .expect("HMAC can take key of any size");
-        mac.update(raw_signature.as_bytes());
-
-        let signature = hex::encode(mac.finalize().into_bytes());
-
-        let mut auth_info = HashMap::new();
-        auth_info.insert("Nonce", nonce);
-        auth_info.insert("APIKey", self.api_key.clone());
-        auth_info.insert("Signature", signature);
-        auth_info.insert("UserId", self.user_id.clone());
+    // Uses an existing session, e.g. one configured for 2FA or shared with another manager
+    pub fn with_auth_session(mut self, auth: AuthSession) -> Self {
+        self.auth = auth;
+        self
+    }
 
-        auth_info
+    pub fn auth(&self) -> &AuthSession {
+        &self.auth
     }
 
-    fn generate_nonce(&self) -> String {
-        let start = SystemTime::now();
-        let since_the_epoch = start
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards");
-        since_the_epoch.as_millis().to_string()
+    pub fn generate_auth_dict(&self) -> HashMap<&str, String> {
+        self.auth.credentials().auth_dict()
     }
 
     fn get_headers(&self) -> HeaderMap {
@@ -82,92 +82,426 @@ impl OrderManager {
         headers
     }
 
-    fn get_auth_headers(&self) -> HeaderMap {
-        let mut headers = self.get_headers();
-        let auth_info = self.generate_auth_dict();
-        for (key, value) in auth_info {
-            headers.insert(
-                HeaderName::from_str(&key).unwrap(),
-                HeaderValue::from_str(&value).unwrap(),
-            );
-        }
-        headers
+    // Signs in now and caches the session token for later calls
+    pub async fn authenticate(&self) -> Result<AuthenticateResponse, NdaxError> {
+        self.auth.authenticate().await
     }
 
-    pub async fn authenticate(&self) -> Result<serde_json::Value, Box<dyn Error>> {
+    pub async fn get_account_id(&self) -> Result<Vec<AccountInfo>, NdaxError> {
         let params = [
-            ("APIKey", &self.api_key),
-            ("Signature", &self.user_id),
-            ("UserId", &self.account_name),
-            ("Nonce", &self.generate_nonce()),
+            ("OMSId", "1"),
+            ("UserId", &self.user_id),
+            ("UserName", &self.account_name),
         ];
 
-        let url = format!("{}{}", self.api_url, constants::AUTHENTICATE_USER_PATH_URL);
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .query(&params)
-            .send()
+        self.get::<Vec<AccountInfo>>(constants::USER_ACCOUNT_INFOS_PATH_URL, &params)
+            .await
+    }
+
+    // Balances of every product held in the account
+    pub async fn get_account_positions(&self) -> Result<Vec<Position>, NdaxError> {
+        let query_params = [("OMSId", "1"), ("AccountId", &self.account_id)];
+
+        self.get::<Vec<Position>>(constants::GET_ACCOUNT_POSITIONS_PATH_URL, &query_params)
+            .await
+    }
+
+    pub async fn cancel_all_orders(&self) -> Result<GenericResponse, NdaxError> {
+        let query_params = [("OMSId", "1"), ("AccountId", &self.account_id)];
+
+        self.get::<GenericResponse>(constants::CANCEL_ALL_ORDERS_PATH_URL, &query_params)
             .await?
-            .json::<serde_json::Value>()
-            .await?;
+            .into_result()
+    }
 
-        Ok(response)
+    pub async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, NdaxError> {
+        let query_params = [("OMSId", "1"), ("AccountId", &self.account_id)];
+
+        self.get::<Vec<OpenOrder>>(constants::GET_OPEN_ORDERS_PATH, &query_params)
+            .await
     }
 
-    pub async fn get_account_id(&self) -> Result<serde_json::Value, Box<dyn Error>> {
-        let params = [
+    // The current state of one order, whether or not it is still open
+    pub async fn get_order_status(&self, order_id: OrderId) -> Result<OpenOrder, NdaxError> {
+        let order_id = order_id.0.to_string();
+        let query_params = [
             ("OMSId", "1"),
-            ("UserId", &self.user_id),
-            ("UserName", &self.account_name),
+            ("AccountId", &self.account_id),
+            ("OrderId", &order_id),
         ];
 
-        let url = format!("{}{}", self.api_url, constants::USER_ACCOUNT_INFOS_PATH_URL);
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .query(&params)
-            .send()
-            .await?
-            .json::<serde_json::Value>()
-            .await?;
+        self.get::<OpenOrder>(constants::GET_ORDER_STATUS_PATH_URL, &query_params)
+            .await
+    }
 
-        Ok(response)
+    /// Places an order after checking it against the instrument's increments.
+    ///
+    /// Orders that fail validation never reach the network; orders NDAX refuses
+    /// come back as `NdaxError::OrderRejected`.
+    pub async fn send_order(
+        &self,
+        order: &NewOrder,
+        increments: &Increments,
+    ) -> Result<SendOrderResponse, NdaxError> {
+        order.validate(increments)?;
+        let payload = order.to_payload(1, self.parse_account_id()?);
+
+        self.post::<SendOrderResponse>(constants::SEND_ORDER_PATH_URL, &payload)
+            .await?
+            .into_result()
     }
 
-    pub async fn cancel_all_orders(&self) -> Result<serde_json::Value, Box<dyn Error>> {
-        let query_params = [("OMSId", "1"), ("AccountId", &self.account_id)];
+    // Cancels a single order by its exchange or client order id
+    pub async fn cancel_order(
+        &self,
+        order: impl Into<OrderRef>,
+    ) -> Result<GenericResponse, NdaxError> {
+        let mut payload = json!({ "OMSId": 1, "AccountId": self.parse_account_id()? });
+        match order.into() {
+            OrderRef::Id(order_id) => payload["OrderId"] = order_id.0.into(),
+            OrderRef::Client(client_order_id) => payload["ClOrderId"] = client_order_id.0.into(),
+        }
 
-        let url = format!("{}{}", self.api_url, constants::CANCEL_ALL_ORDERS_PATH_URL);
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .query(&query_params)
-            .send()
+        self.post::<GenericResponse>(constants::CANCEL_ORDER_PATH_URL, &payload)
             .await?
-            .json::<serde_json::Value>()
-            .await?;
+            .into_result()
+    }
 
-        Ok(response)
+    /// Atomically cancels `order_id` and places `replacement` in its place.
+    ///
+    /// The replacement is validated like a new order before anything is sent.
+    pub async fn cancel_replace_order(
+        &self,
+        order_id: OrderId,
+        replacement: &NewOrder,
+        increments: &Increments,
+    ) -> Result<CancelReplaceResponse, NdaxError> {
+        replacement.validate(increments)?;
+        let payload = replacement.to_replace_payload(1, self.parse_account_id()?, order_id);
+
+        self.post::<CancelReplaceResponse>(constants::CANCEL_REPLACE_ORDER_PATH_URL, &payload)
+            .await
     }
 
-    pub async fn get_open_orders(&self) -> Result<serde_json::Value, Box<dyn Error>> {
-        let query_params = [("OMSId", "1"), ("AccountId", &self.account_id)];
+    /// Changes the quantity of a working order without losing its place in the book.
+    ///
+    /// NDAX only allows the quantity to be reduced this way; use
+    /// `cancel_replace_order` to change the price or increase the size.
+    pub async fn modify_order(
+        &self,
+        order_id: OrderId,
+        instrument_id: u64,
+        previous_order_revision: u64,
+        quantity: Decimal,
+        increments: &Increments,
+    ) -> Result<GenericResponse, NdaxError> {
+        if !increments.is_valid_quantity(quantity) {
+            return Err(NdaxError::InvalidOrder(format!(
+                "quantity {} is not a multiple of {} of at least {}",
+                quantity, increments.quantity_increment, increments.min_quantity
+            )));
+        }
+        let request = ModifyOrderRequest {
+            oms_id: 1,
+            order_id,
+            instrument_id,
+            previous_order_revision,
+            quantity,
+            account_id: self.parse_account_id()?,
+        };
+        let payload = serde_json::to_value(request)?;
 
-        let url = format!("{}{}", self.api_url, constants::GET_OPEN_ORDERS_PATH);
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .query(&query_params)
-            .send()
+        self.post::<GenericResponse>(constants::MODIFY_ORDER_PATH_URL, &payload)
             .await?
-            .json::<serde_json::Value>()
-            .await?;
+            .into_result()
+    }
+
+    // Every fill of the account's orders
+    pub fn get_account_trades(
+        &self,
+        query: &HistoryQuery,
+    ) -> impl Stream<Item = Result<OrderTradeEvent, NdaxError>> {
+        self.paginate(constants::GET_ACCOUNT_TRADES_PATH_URL, "Count", query)
+    }
+
+    // Every order the account has placed, whatever its final state
+    pub fn get_orders_history(
+        &self,
+        query: &HistoryQuery,
+    ) -> impl Stream<Item = Result<OpenOrder, NdaxError>> {
+        self.paginate(constants::GET_ORDERS_HISTORY_PATH_URL, "Depth", query)
+    }
+
+    // The account's trades, filtered by instrument and time range
+    pub fn get_trades_history(
+        &self,
+        query: &HistoryQuery,
+    ) -> impl Stream<Item = Result<OrderTradeEvent, NdaxError>> {
+        self.paginate(constants::GET_TRADES_HISTORY_PATH_URL, "Depth", query)
+    }
+
+    // Every ledger entry: trades, fees, deposits and withdrawals
+    pub fn get_account_transactions(
+        &self,
+        query: &HistoryQuery,
+    ) -> impl Stream<Item = Result<AccountTransaction, NdaxError>> {
+        self.paginate(constants::GET_ACCOUNT_TRANSACTIONS_PATH_URL, "Depth", query)
+    }
+
+    // Walks a StartIndex-paginated history endpoint, see `paginate_pages`
+    fn paginate<T>(
+        &self,
+        path: &'static str,
+        page_size_param: &'static str,
+        query: &HistoryQuery,
+    ) -> impl Stream<Item = Result<T, NdaxError>>
+    where
+        T: DeserializeOwned + Clone + PartialEq,
+    {
+        let order_manager = self.clone();
+        let query = query.clone();
+        paginate_pages(move |start_index| {
+            let order_manager = order_manager.clone();
+            let query = query.clone();
+            async move {
+                order_manager
+                    .fetch_page(path, page_size_param, &query, start_index)
+                    .await
+            }
+        })
+    }
+
+    async fn fetch_page<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        page_size_param: &str,
+        query: &HistoryQuery,
+        start_index: usize,
+    ) -> Result<Vec<T>, NdaxError> {
+        let mut params = vec![
+            ("OMSId", "1".to_string()),
+            ("AccountId", self.account_id.clone()),
+            ("StartIndex", start_index.to_string()),
+            (page_size_param, query.page_size.to_string()),
+        ];
+        if let Some(instrument_id) = query.instrument_id {
+            params.push(("InstrumentId", instrument_id.to_string()));
+        }
+        if let Some(start_time) = query.start_time {
+            params.push(("StartTimeStamp", start_time.to_string()));
+        }
+        if let Some(end_time) = query.end_time {
+            params.push(("EndTimeStamp", end_time.to_string()));
+        }
+        let params: Vec<(&str, &str)> = params
+            .iter()
+            .map(|(key, value)| (*key, value.as_str()))
+            .collect();
+
+        self.get::<Vec<T>>(path, &params).await
+    }
+
+    // Sends an authenticated GET with query parameters and decodes the reply
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, NdaxError> {
+        let url = format!("{}{}", self.api_url, path);
+        self.send_authorized(|| self.client.get(&url).query(query))
+            .await
+    }
+
+    // Sends an authenticated POST with a JSON body and decodes the reply
+    async fn post<T: DeserializeOwned>(&self, path: &str, payload: &Value) -> Result<T, NdaxError> {
+        let url = format!("{}{}", self.api_url, path);
+        self.send_authorized(|| self.client.post(&url).json(payload))
+            .await
+    }
+
+    // Sends a request with the session token, signing in again once if it was refused
+    async fn send_authorized<T, F>(&self, request: F) -> Result<T, NdaxError>
+    where
+        T: DeserializeOwned,
+        F: Fn() -> RequestBuilder,
+    {
+        let mut retried = false;
+        loop {
+            let mut headers = self.get_headers();
+            headers.extend(self.auth.headers().await?);
+            let response = request().headers(headers).send().await?;
+            match parse_response::<T>(response).await {
+                // Only an expired or invalid session is worth signing in again for
+                Err(NdaxError::SessionRejected(_)) if !retried => {
+                    self.auth.invalidate().await;
+                    retried = true;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub(crate) fn parse_account_id(&self) -> Result<u64, NdaxError> {
+        self.account_id
+            .parse()
+            .map_err(|_| NdaxError::Config(format!("invalid account id {}", self.account_id)))
+    }
+}
+
+// The quantity goes on the wire as a JSON number, like SendOrder's
+#[derive(Serialize)]
+struct ModifyOrderRequest {
+    #[serde(rename = "OMSId")]
+    oms_id: u64,
+    #[serde(rename = "OrderId")]
+    order_id: OrderId,
+    #[serde(rename = "InstrumentId")]
+    instrument_id: u64,
+    #[serde(rename = "PreviousOrderRevision")]
+    previous_order_revision: u64,
+    #[serde(rename = "Quantity", with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+    #[serde(rename = "AccountId")]
+    account_id: u64,
+}
+
+/// Streams the items of consecutive pages, fetching a page by its StartIndex.
+///
+/// The next page is only requested once the previous one has been consumed.
+/// The server may cap a page below the requested size, so a short page does
+/// not mean the history is exhausted; the stream ends at the first empty page.
+/// It also ends at a page that repeats the one before, as a server ignoring
+/// StartIndex would otherwise be paged through forever.
+fn paginate_pages<T, F, Fut>(fetch_page: F) -> impl Stream<Item = Result<T, NdaxError>>
+where
+    T: Clone + PartialEq,
+    F: Fn(usize) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, NdaxError>>,
+{
+    stream::try_unfold(Some((0, Vec::new())), move |next| {
+        let page = next
+            .as_ref()
+            .map(|(start_index, _)| fetch_page(*start_index));
+        async move {
+            let (Some((start_index, previous)), Some(page)) = (next, page) else {
+                return Ok(None);
+            };
+            let page = page.await?;
+            if page.is_empty() || page == previous {
+                return Ok(None);
+            }
+            let next = Some((start_index + page.len(), page.clone()));
+            let items = stream::iter(page.into_iter().map(Ok::<T, NdaxError>));
+            Ok::<_, NdaxError>(Some((items, next)))
+        }
+    })
+    .try_flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::canned_server;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_modify_order_sends_quantity_as_number() {
+        let request = ModifyOrderRequest {
+            oms_id: 1,
+            order_id: OrderId(6713),
+            instrument_id: 1,
+            previous_order_revision: 2,
+            quantity: Decimal::new(5, 1),
+            account_id: 7,
+        };
+        let payload = serde_json::to_value(request).unwrap();
+        assert_eq!(payload["Quantity"], json!(0.5));
+        assert_eq!(payload["OrderId"], json!(6713));
+    }
+
+    fn order_manager(api_url: &str) -> OrderManager {
+        OrderManager::new(api_url, "key", "secret", "170", "trader", "185")
+    }
+
+    #[tokio::test]
+    async fn test_signs_in_again_once_when_the_session_is_rejected() {
+        let signed_in = |token: &str| (200, json!({"Authenticated": true, "SessionToken": token}));
+        let (api_url, requests) = canned_server(vec![
+            signed_in("expired"),
+            (401, json!({"message": "session expired"})),
+            signed_in("fresh"),
+            (200, json!([])),
+        ])
+        .await;
+        let orders = order_manager(&api_url).get_open_orders().await.unwrap();
+        assert!(orders.is_empty());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[1].contains("aptoken: expired"));
+        assert!(requests[2].starts_with("GET /AuthenticateUser"));
+        assert!(requests[3].starts_with("GET /GetOpenOrders"));
+        assert!(requests[3].contains("aptoken: fresh"));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_when_a_fresh_session_is_rejected_too() {
+        let signed_in = |token: &str| (200, json!({"Authenticated": true, "SessionToken": token}));
+        let (api_url, requests) = canned_server(vec![
+            signed_in("first"),
+            (401, json!({})),
+            signed_in("second"),
+            (401, json!({})),
+        ])
+        .await;
+        let result = order_manager(&api_url).get_open_orders().await;
+        assert!(matches!(result, Err(NdaxError::SessionRejected(_))));
+        assert_eq!(requests.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_paginates_past_short_pages_until_an_empty_one() {
+        // The server caps pages at 3 items, whatever page size was asked for
+        let history: Vec<u64> = (0..7).collect();
+        let requested = Mutex::new(Vec::new());
+        let items: Vec<u64> = paginate_pages(|start_index| {
+            requested.lock().unwrap().push(start_index);
+            let page = history.iter().skip(start_index).take(3).copied().collect();
+            async move { Ok(page) }
+        })
+        .try_collect()
+        .await
+        .unwrap();
+
+        assert_eq!(items, history);
+        assert_eq!(*requested.lock().unwrap(), vec![0, 3, 6, 7]);
+    }
+
+    #[tokio::test]
+    async fn test_pagination_stops_when_start_index_is_ignored() {
+        let requested = Mutex::new(Vec::new());
+        let items: Vec<u64> = paginate_pages(|start_index| {
+            requested.lock().unwrap().push(start_index);
+            async move { Ok(vec![1, 2, 3]) }
+        })
+        .try_collect()
+        .await
+        .unwrap();
+
+        assert_eq!(items, vec![1, 2, 3]);
+        assert_eq!(*requested.lock().unwrap(), vec![0, 3]);
+    }
 
-        Ok(response)
+    #[tokio::test]
+    async fn test_pagination_stops_at_the_first_error() {
+        let result: Result<Vec<u64>, NdaxError> = paginate_pages(|start_index| async move {
+            match start_index {
+                0 => Ok(vec![1, 2]),
+                _ => Err(NdaxError::ConnectionClosed),
+            }
+        })
+        .try_collect()
+        .await;
+        assert!(matches!(result, Err(NdaxError::ConnectionClosed)));
     }
 }
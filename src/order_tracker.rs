@@ -0,0 +1,493 @@
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+
+use crate::account_session::AccountEvent;
+use crate::constants;
+use crate::entities::cancel_order_reject_event::CancelOrderRejectEvent;
+use crate::entities::new_order::NewOrder;
+use crate::entities::open_order::OpenOrder;
+use crate::entities::order_trade_event::OrderTradeEvent;
+use crate::entities::order_types::{ClientOrderId, OrderId};
+use crate::error::NdaxError;
+use crate::frame::Frame;
+use crate::order_manager::OrderManager;
+
+/// Where an order is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderState {
+    // Accepted by SendOrder but not yet confirmed by an OrderStateEvent
+    PendingNew,
+    Working,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+    Expired,
+}
+
+impl OrderState {
+    // Whether the order can no longer change
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Canceled | OrderState::Rejected | OrderState::Expired
+        )
+    }
+
+    // Maps NDAX's OrderState string, using the executed quantity to spot partial fills
+    fn from_exchange(order_state: &str, quantity_executed: Decimal) -> Option<Self> {
+        match order_state {
+            "Working" if quantity_executed > Decimal::ZERO => Some(OrderState::PartiallyFilled),
+            "Working" => Some(OrderState::Working),
+            "FullyExecuted" => Some(OrderState::Filled),
+            "Canceled" => Some(OrderState::Canceled),
+            "Rejected" => Some(OrderState::Rejected),
+            "Expired" => Some(OrderState::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// What the tracker knows about one order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedOrder {
+    pub order_id: OrderId,
+    pub client_order_id: ClientOrderId,
+    pub instrument_id: u64,
+    pub side: String,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub filled_quantity: Decimal,
+    pub avg_price: Decimal,
+    pub state: OrderState,
+    pub order_revision: u64,
+    pub last_updated_time: u64,
+    pub reject_reason: Option<String>,
+    // Reason given by the last CancelOrderRejectEvent, if any
+    pub cancel_reject_reason: Option<String>,
+}
+
+impl TrackedOrder {
+    fn from_open_order(order: &OpenOrder) -> Self {
+        TrackedOrder {
+            order_id: order.order_id,
+            client_order_id: ClientOrderId(order.client_order_id),
+            instrument_id: order.instrument_id,
+            side: order.side.clone(),
+            price: order.price,
+            quantity: order.orig_quantity,
+            filled_quantity: Decimal::ZERO,
+            avg_price: Decimal::ZERO,
+            state: OrderState::PendingNew,
+            order_revision: order.order_revision,
+            last_updated_time: order.last_updated_time,
+            reject_reason: None,
+            cancel_reject_reason: None,
+        }
+    }
+
+    pub fn remaining_quantity(&self) -> Decimal {
+        (self.quantity - self.filled_quantity).max(Decimal::ZERO)
+    }
+
+    fn set_state(&mut self, state: OrderState) {
+        // Late events must not reopen an order that has already finished
+        if !self.state.is_terminal() {
+            self.state = state;
+        }
+    }
+}
+
+/// Local record of the account's orders, kept current from account events.
+///
+/// Feed it every gateway frame with `handle_frame`; call `reconcile_with` on
+/// startup and after a reconnect to catch up on anything missed.
+///
+/// An order's filled quantity only ever grows. OrderStateEvents report it as
+/// QuantityExecuted and OrderTradeEvents as the quantity minus what remains,
+/// so a fill reported both ways, or replayed after a reconnect, counts once.
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    orders: HashMap<OrderId, TrackedOrder>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        OrderTracker::default()
+    }
+
+    pub fn get(&self, order_id: OrderId) -> Option<&TrackedOrder> {
+        self.orders.get(&order_id)
+    }
+
+    pub fn find_by_client_order_id(&self, client_order_id: ClientOrderId) -> Option<&TrackedOrder> {
+        self.orders
+            .values()
+            .find(|order| order.client_order_id == client_order_id)
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values()
+    }
+
+    // Orders that may still trade
+    pub fn open_orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders
+            .values()
+            .filter(|order| !order.state.is_terminal())
+    }
+
+    // Records an order SendOrder accepted, before its first OrderStateEvent
+    pub fn track_new(&mut self, order_id: OrderId, order: &NewOrder) {
+        self.orders.entry(order_id).or_insert_with(|| TrackedOrder {
+            order_id,
+            client_order_id: order.client_order_id.unwrap_or(ClientOrderId(0)),
+            instrument_id: order.instrument_id,
+            side: format!("{:?}", order.side),
+            price: order.limit_price.unwrap_or_default(),
+            quantity: order.quantity,
+            filled_quantity: Decimal::ZERO,
+            avg_price: Decimal::ZERO,
+            state: OrderState::PendingNew,
+            order_revision: 0,
+            last_updated_time: 0,
+            reject_reason: None,
+            cancel_reject_reason: None,
+        });
+    }
+
+    /// Routes account event frames to the orders they concern.
+    ///
+    /// Returns the order that changed, if the frame was an order event.
+    pub fn handle_frame(&mut self, frame: &Frame) -> Result<Option<OrderId>, NdaxError> {
+        let order_id = match frame.function_name.as_str() {
+            constants::ORDER_STATE_EVENT => self.apply_order_state(&frame.payload_as()?),
+            constants::ORDER_TRADE_EVENT => self.apply_trade(&frame.payload_as()?),
+            constants::CANCEL_ORDER_REJECT_EVENT => self.apply_cancel_reject(&frame.payload_as()?),
+            _ => return Ok(None),
+        };
+        Ok(Some(order_id))
+    }
+
+    // Applies an event from an AccountSession, returning the order it changed
+    pub fn apply_event(&mut self, event: &AccountEvent) -> Option<OrderId> {
+        match event {
+            AccountEvent::OrderState(order) => Some(self.apply_order_state(order)),
+            AccountEvent::Trade(trade) => Some(self.apply_trade(trade)),
+            AccountEvent::CancelOrderReject(reject) => Some(self.apply_cancel_reject(reject)),
+            _ => None,
+        }
+    }
+
+    pub fn apply_order_state(&mut self, event: &OpenOrder) -> OrderId {
+        let order_id = event.order_id;
+        let order = self
+            .orders
+            .entry(order_id)
+            .or_insert_with(|| TrackedOrder::from_open_order(event));
+        // An event older than the order we know of would undo a modify
+        if event.order_revision < order.order_revision {
+            return order_id;
+        }
+
+        order.price = event.price;
+        order.quantity = event.orig_quantity;
+        order.order_revision = event.order_revision;
+        order.last_updated_time = order.last_updated_time.max(event.last_updated_time);
+        // The exchange's totals win when it has seen fills we have not
+        if event.quantity_executed > order.filled_quantity {
+            order.filled_quantity = event.quantity_executed;
+            order.avg_price = event.avg_price;
+        }
+        if let Some(reason) = event.reject_reason.as_ref().filter(|r| !r.is_empty()) {
+            order.reject_reason = Some(reason.clone());
+        }
+        if let Some(state) = OrderState::from_exchange(&event.order_state, order.filled_quantity) {
+            order.set_state(state);
+        }
+        order_id
+    }
+
+    pub fn apply_trade(&mut self, event: &OrderTradeEvent) -> OrderId {
+        let order = self
+            .orders
+            .entry(event.order_id)
+            .or_insert_with(|| TrackedOrder {
+                order_id: event.order_id,
+                client_order_id: event.client_order_id(),
+                instrument_id: event.instrument_id,
+                side: event.side.clone(),
+                price: event.price,
+                quantity: event.quantity + event.remaining_quantity,
+                filled_quantity: Decimal::ZERO,
+                avg_price: Decimal::ZERO,
+                state: OrderState::Working,
+                order_revision: 0,
+                last_updated_time: 0,
+                reject_reason: None,
+                cancel_reject_reason: None,
+            });
+        // A finished order's totals already include every fill the exchange made
+        if order.state.is_terminal() {
+            return event.order_id;
+        }
+        // The total after this trade, which earlier state events or trades
+        // may already have reported
+        let filled_quantity = (order.quantity - event.remaining_quantity).max(Decimal::ZERO);
+        if filled_quantity > order.filled_quantity {
+            let value = order.avg_price * order.filled_quantity
+                + event.price * (filled_quantity - order.filled_quantity);
+            order.filled_quantity = filled_quantity;
+            order.avg_price = value / filled_quantity;
+        }
+        order.last_updated_time = order.last_updated_time.max(event.trade_time_ms);
+        if order.filled_quantity >= order.quantity {
+            order.set_state(OrderState::Filled);
+        } else {
+            order.set_state(OrderState::PartiallyFilled);
+        }
+        event.order_id
+    }
+
+    pub fn apply_cancel_reject(&mut self, event: &CancelOrderRejectEvent) -> OrderId {
+        if let Some(order) = self.orders.get_mut(&event.order_id) {
+            order.cancel_reject_reason = Some(event.reject_reason.clone());
+        }
+        event.order_id
+    }
+
+    /// Brings the tracker in line with the exchange's list of open orders.
+    ///
+    /// Listed orders are updated in place. Returns the tracked orders that are
+    /// still open locally but missing from the list: they finished while we
+    /// were not listening, and their final state has to be looked up.
+    pub fn reconcile(&mut self, open_orders: &[OpenOrder]) -> Vec<OrderId> {
+        for open_order in open_orders {
+            self.apply_order_state(open_order);
+        }
+
+        let listed: HashSet<OrderId> = open_orders.iter().map(|order| order.order_id).collect();
+        let mut missing: Vec<OrderId> = self
+            .open_orders()
+            .map(|order| order.order_id)
+            .filter(|order_id| !listed.contains(order_id))
+            .collect();
+        missing.sort_unstable();
+        missing
+    }
+
+    // Fetches GetOpenOrders and reconciles against it, e.g. on startup or reconnect,
+    // then asks GetOrderStatus how each missing order ended. Returns those orders.
+    pub async fn reconcile_with(
+        &mut self,
+        order_manager: &OrderManager,
+    ) -> Result<Vec<OrderId>, NdaxError> {
+        let open_orders = order_manager.get_open_orders().await?;
+        let missing = self.reconcile(&open_orders);
+        for order_id in &missing {
+            let status = order_manager.get_order_status(*order_id).await?;
+            self.apply_order_state(&status);
+        }
+        Ok(missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::level2_entry::Side;
+    use serde_json::{json, Value};
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn event(function_name: &str, payload: Value) -> Frame {
+        Frame::decode(
+            &json!({ "m": 3, "i": 0, "n": function_name, "o": payload.to_string() }).to_string(),
+        )
+        .unwrap()
+    }
+
+    fn order_state(order_id: u64, state: &str, executed: f64, avg_price: f64) -> Frame {
+        event(
+            "OrderStateEvent",
+            json!({
+                "OMSId": 1, "OrderId": order_id, "ClientOrderId": 42, "Account": 185,
+                "Instrument": 1, "Side": "Buy", "OrderType": "Limit", "OrderState": state,
+                "Price": 5711.8, "Quantity": 0.25, "OrigQuantity": 0.25,
+                "QuantityExecuted": executed, "AvgPrice": avg_price,
+                "ReceiveTime": 1718007168597u64, "LastUpdatedTime": 1718007168600u64,
+                "OrderRevision": 1
+            }),
+        )
+    }
+
+    fn trade(trade_id: u64, quantity: f64, remaining: f64, price: f64) -> Frame {
+        event(
+            "OrderTradeEvent",
+            json!({
+                "OMSId": 1, "TradeId": trade_id, "OrderId": 6713, "ClientOrderId": 42,
+                "AccountId": 185, "InstrumentId": 1, "Side": "Buy", "Quantity": quantity,
+                "RemainingQuantity": remaining, "Price": price, "TradeTimeMS": 1718007169000u64
+            }),
+        )
+    }
+
+    #[test]
+    fn test_lifecycle_with_fills() {
+        let mut tracker = OrderTracker::new();
+        let order = NewOrder::limit(1, Side::Buy, dec("0.25"), dec("5711.8"))
+            .with_client_order_id(ClientOrderId(42));
+        tracker.track_new(OrderId(6713), &order);
+        assert_eq!(
+            tracker.get(OrderId(6713)).unwrap().state,
+            OrderState::PendingNew
+        );
+
+        tracker
+            .handle_frame(&order_state(6713, "Working", 0.0, 0.0))
+            .unwrap();
+        assert_eq!(
+            tracker.get(OrderId(6713)).unwrap().state,
+            OrderState::Working
+        );
+
+        tracker.handle_frame(&trade(1, 0.1, 0.15, 5711.8)).unwrap();
+        // The same fill delivered twice only counts once
+        tracker.handle_frame(&trade(1, 0.1, 0.15, 5711.8)).unwrap();
+        let tracked = tracker.get(OrderId(6713)).unwrap();
+        assert_eq!(tracked.state, OrderState::PartiallyFilled);
+        assert_eq!(tracked.filled_quantity, dec("0.1"));
+
+        tracker.handle_frame(&trade(2, 0.15, 0.0, 5711.3)).unwrap();
+        let tracked = tracker.find_by_client_order_id(ClientOrderId(42)).unwrap();
+        assert_eq!(tracked.state, OrderState::Filled);
+        assert_eq!(tracked.filled_quantity, dec("0.25"));
+        assert_eq!(tracked.avg_price, dec("5711.5"));
+
+        // A stale Working event cannot reopen a filled order
+        tracker
+            .handle_frame(&order_state(6713, "Working", 0.1, 5711.8))
+            .unwrap();
+        assert_eq!(
+            tracker.get(OrderId(6713)).unwrap().state,
+            OrderState::Filled
+        );
+        assert_eq!(tracker.open_orders().count(), 0);
+    }
+
+    #[test]
+    fn test_fill_reported_by_state_and_trade_counts_once() {
+        let mut tracker = OrderTracker::new();
+        tracker
+            .handle_frame(&order_state(6713, "Working", 0.1, 5711.8))
+            .unwrap();
+        tracker.handle_frame(&trade(1, 0.1, 0.15, 5711.8)).unwrap();
+        let tracked = tracker.get(OrderId(6713)).unwrap();
+        assert_eq!(tracked.state, OrderState::PartiallyFilled);
+        assert_eq!(tracked.filled_quantity, dec("0.1"));
+        assert_eq!(tracked.avg_price, dec("5711.8"));
+
+        tracker.handle_frame(&trade(2, 0.15, 0.0, 5711.3)).unwrap();
+        let tracked = tracker.get(OrderId(6713)).unwrap();
+        assert_eq!(tracked.state, OrderState::Filled);
+        assert_eq!(tracked.filled_quantity, dec("0.25"));
+        assert_eq!(tracked.avg_price, dec("5711.5"));
+    }
+
+    #[test]
+    fn test_trade_after_reconcile_adds_to_reported_fills() {
+        let mut tracker = OrderTracker::new();
+        let open_order: OpenOrder = order_state(6713, "Working", 0.1, 5711.8)
+            .payload_as()
+            .unwrap();
+        tracker.reconcile(&[open_order]);
+
+        // The trade events for the first 0.1 were missed while disconnected
+        tracker.handle_frame(&trade(2, 0.1, 0.05, 5711.3)).unwrap();
+        let tracked = tracker.get(OrderId(6713)).unwrap();
+        assert_eq!(tracked.state, OrderState::PartiallyFilled);
+        assert_eq!(tracked.filled_quantity, dec("0.2"));
+        assert_eq!(tracked.avg_price, dec("5711.55"));
+    }
+
+    #[test]
+    fn test_older_revision_is_ignored() {
+        let mut tracker = OrderTracker::new();
+        let mut modified: OpenOrder = order_state(6713, "Working", 0.0, 0.0).payload_as().unwrap();
+        modified.order_revision = 2;
+        modified.price = dec("5700");
+        modified.orig_quantity = dec("0.2");
+        tracker.apply_order_state(&modified);
+
+        tracker
+            .handle_frame(&order_state(6713, "Canceled", 0.05, 5711.8))
+            .unwrap();
+        let tracked = tracker.get(OrderId(6713)).unwrap();
+        assert_eq!(tracked.state, OrderState::Working);
+        assert_eq!(tracked.price, dec("5700"));
+        assert_eq!(tracked.quantity, dec("0.2"));
+        assert_eq!(tracked.filled_quantity, Decimal::ZERO);
+        assert_eq!(tracked.order_revision, 2);
+    }
+
+    #[test]
+    fn test_cancel_reject_keeps_order_working() {
+        let mut tracker = OrderTracker::new();
+        tracker
+            .handle_frame(&order_state(6713, "Working", 0.0, 0.0))
+            .unwrap();
+        let reject = event(
+            "CancelOrderRejectEvent",
+            json!({
+                "OMSId": 1, "AccountId": 185, "OrderId": 6713, "OrderRevision": 1,
+                "OrderType": "Limit", "InstrumentId": 1, "Status": "Rejected",
+                "RejectReason": "Order Not Found"
+            }),
+        );
+        assert_eq!(tracker.handle_frame(&reject).unwrap(), Some(OrderId(6713)));
+        let tracked = tracker.get(OrderId(6713)).unwrap();
+        assert_eq!(tracked.state, OrderState::Working);
+        assert_eq!(
+            tracked.cancel_reject_reason.as_deref(),
+            Some("Order Not Found")
+        );
+    }
+
+    #[test]
+    fn test_reconcile_reports_missing_orders() {
+        let mut tracker = OrderTracker::new();
+        tracker
+            .handle_frame(&order_state(6713, "Working", 0.0, 0.0))
+            .unwrap();
+        tracker
+            .handle_frame(&order_state(6714, "Working", 0.0, 0.0))
+            .unwrap();
+        tracker
+            .handle_frame(&order_state(6715, "Canceled", 0.0, 0.0))
+            .unwrap();
+
+        let still_open: OpenOrder = order_state(6714, "Working", 0.05, 5711.8)
+            .payload_as()
+            .unwrap();
+        assert_eq!(tracker.reconcile(&[still_open]), vec![OrderId(6713)]);
+        // Left for GetOrderStatus to settle rather than guessed
+        assert_eq!(
+            tracker.get(OrderId(6713)).unwrap().state,
+            OrderState::Working
+        );
+        let filled: OpenOrder = order_state(6713, "FullyExecuted", 0.25, 5711.8)
+            .payload_as()
+            .unwrap();
+        tracker.apply_order_state(&filled);
+        assert_eq!(
+            tracker.get(OrderId(6713)).unwrap().state,
+            OrderState::Filled
+        );
+        let partially_filled = tracker.get(OrderId(6714)).unwrap();
+        assert_eq!(partially_filled.state, OrderState::PartiallyFilled);
+        assert_eq!(partially_filled.filled_quantity, dec("0.05"));
+    }
+}
@@ -0,0 +1,396 @@
+use arrow_array::{
+    ArrayRef, BooleanArray, Decimal128Array, RecordBatch, StringArray, TimestampMillisecondArray,
+    UInt64Array, UInt8Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::csv_export::write_csv;
+use crate::entities::level2_entry::{ActionType, Side};
+use crate::entities::trade_event::TradeEvent;
+use crate::error::NdaxError;
+use crate::instrument_catalogue::InstrumentCatalogue;
+use crate::recorder::{L2Record, L2RecordKind};
+
+const DECIMAL_PRECISION: u8 = 38;
+// NDAX quotes prices and quantities to at most 8 decimal places
+const DECIMAL_SCALE: i8 = 8;
+const UTC: &str = "UTC";
+
+/// File format for exported trades and Level2 rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = NdaxError;
+
+    // Parses "csv" or "parquet", ignoring case, e.g. from an environment variable
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(NdaxError::Config(format!(
+                "unknown export format {}",
+                format
+            ))),
+        }
+    }
+}
+
+/// One Level2 entry of a recorded frame, flattened for tabular export.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct L2Row {
+    #[serde(rename = "ReceivedAt")]
+    pub received_at: u64,
+    #[serde(rename = "Kind")]
+    pub kind: L2RecordKind,
+    #[serde(rename = "InstrumentId")]
+    pub instrument_id: u64,
+    #[serde(rename = "Symbol")]
+    pub symbol: Option<String>,
+    #[serde(rename = "MDUpdateId")]
+    pub md_update_id: u64,
+    #[serde(rename = "Accounts")]
+    pub accounts: u64,
+    #[serde(rename = "ActionDateTime")]
+    pub action_date_time: u64,
+    #[serde(rename = "ActionType")]
+    pub action_type: ActionType,
+    #[serde(rename = "LastTradePrice")]
+    pub last_trade_price: Decimal,
+    #[serde(rename = "Orders")]
+    pub orders: u64,
+    #[serde(rename = "Price")]
+    pub price: Decimal,
+    #[serde(rename = "Quantity")]
+    pub quantity: Decimal,
+    #[serde(rename = "Side")]
+    pub side: Side,
+}
+
+// One row per entry of every record, with the symbol looked up in the catalogue
+pub fn l2_rows(records: &[L2Record], catalogue: &InstrumentCatalogue) -> Vec<L2Row> {
+    records
+        .iter()
+        .flat_map(|record| {
+            let symbol = symbol_of(catalogue, record.instrument_id);
+            record.entries.iter().map(move |entry| L2Row {
+                received_at: record.received_at,
+                kind: record.kind,
+                instrument_id: record.instrument_id,
+                symbol: symbol.clone(),
+                md_update_id: entry.md_update_id,
+                accounts: entry.accounts,
+                action_date_time: entry.action_date_time,
+                action_type: entry.action_type,
+                last_trade_price: entry.last_trade_price,
+                orders: entry.orders,
+                price: entry.price,
+                quantity: entry.quantity,
+                side: entry.side,
+            })
+        })
+        .collect()
+}
+
+/// Writes trades to a new file in the given format, returning how many were written.
+///
+/// CSV keeps the columns of `TradeEvent`; Parquet adds the instrument symbol
+/// and stores typed timestamps and decimals.
+pub fn export_trades<P: AsRef<Path>>(
+    path: P,
+    format: ExportFormat,
+    trades: &[TradeEvent],
+    catalogue: &InstrumentCatalogue,
+) -> Result<usize, NdaxError> {
+    let file = File::create(path)?;
+    match format {
+        ExportFormat::Csv => write_csv(file, trades),
+        ExportFormat::Parquet => write_trades_parquet(file, trades, catalogue),
+    }
+}
+
+// Writes recorded Level2 frames to a new file in the given format, one row per entry
+pub fn export_l2<P: AsRef<Path>>(
+    path: P,
+    format: ExportFormat,
+    records: &[L2Record],
+    catalogue: &InstrumentCatalogue,
+) -> Result<usize, NdaxError> {
+    let rows = l2_rows(records, catalogue);
+    let file = File::create(path)?;
+    match format {
+        ExportFormat::Csv => write_csv(file, &rows),
+        ExportFormat::Parquet => write_l2_parquet(file, &rows),
+    }
+}
+
+pub fn trade_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("TradeId", DataType::UInt64, false),
+        Field::new("InstrumentId", DataType::UInt64, false),
+        Field::new("Symbol", DataType::Utf8, true),
+        Field::new("Quantity", decimal_type(), false),
+        Field::new("Price", decimal_type(), false),
+        Field::new("Order1", DataType::UInt64, false),
+        Field::new("Order2", DataType::UInt64, false),
+        Field::new("TradeTime", timestamp_type(), false),
+        Field::new("Side", DataType::UInt8, false),
+        Field::new("TakerSide", DataType::UInt8, false),
+        Field::new("IsBlockTrade", DataType::Boolean, false),
+        Field::new("OrderClientId", DataType::UInt64, false),
+    ]))
+}
+
+pub fn l2_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("ReceivedAt", timestamp_type(), false),
+        Field::new("Kind", DataType::Utf8, false),
+        Field::new("InstrumentId", DataType::UInt64, false),
+        Field::new("Symbol", DataType::Utf8, true),
+        Field::new("MDUpdateId", DataType::UInt64, false),
+        Field::new("Accounts", DataType::UInt64, false),
+        Field::new("ActionDateTime", timestamp_type(), false),
+        Field::new("ActionType", DataType::UInt8, false),
+        Field::new("LastTradePrice", decimal_type(), false),
+        Field::new("Orders", DataType::UInt64, false),
+        Field::new("Price", decimal_type(), false),
+        Field::new("Quantity", decimal_type(), false),
+        Field::new("Side", DataType::UInt8, false),
+    ]))
+}
+
+// Writes trades as one Parquet row group with the schema of `trade_schema`
+pub fn write_trades_parquet<W: Write + Send>(
+    writer: W,
+    trades: &[TradeEvent],
+    catalogue: &InstrumentCatalogue,
+) -> Result<usize, NdaxError> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(
+            trades.iter().map(|t| t.trade_id),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            trades.iter().map(|t| t.instrument_id),
+        )),
+        Arc::new(StringArray::from_iter(
+            trades.iter().map(|t| symbol_of(catalogue, t.instrument_id)),
+        )),
+        decimal_array(trades.iter().map(|t| t.quantity))?,
+        decimal_array(trades.iter().map(|t| t.price))?,
+        Arc::new(UInt64Array::from_iter_values(
+            trades.iter().map(|t| t.order_id_1),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            trades.iter().map(|t| t.order_id_2),
+        )),
+        timestamp_array(trades.iter().map(|t| t.timestamp)),
+        Arc::new(UInt8Array::from_iter_values(trades.iter().map(|t| t.side))),
+        Arc::new(UInt8Array::from_iter_values(
+            trades.iter().map(|t| t.taker_side),
+        )),
+        Arc::new(BooleanArray::from_iter(
+            trades.iter().map(|t| Some(t.is_block_trade != 0)),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            trades.iter().map(|t| t.client_id),
+        )),
+    ];
+    write_parquet(writer, trade_schema(), columns)
+}
+
+// Writes flattened Level2 rows as one Parquet row group with the schema of `l2_schema`
+pub fn write_l2_parquet<W: Write + Send>(writer: W, rows: &[L2Row]) -> Result<usize, NdaxError> {
+    let columns: Vec<ArrayRef> = vec![
+        timestamp_array(rows.iter().map(|r| r.received_at)),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(
+            |r| match r.kind {
+                L2RecordKind::Snapshot => "Snapshot",
+                L2RecordKind::Delta => "Delta",
+            },
+        ))),
+        Arc::new(UInt64Array::from_iter_values(
+            rows.iter().map(|r| r.instrument_id),
+        )),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|r| r.symbol.as_deref()),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            rows.iter().map(|r| r.md_update_id),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            rows.iter().map(|r| r.accounts),
+        )),
+        timestamp_array(rows.iter().map(|r| r.action_date_time)),
+        Arc::new(UInt8Array::from_iter_values(
+            rows.iter().map(|r| u8::from(r.action_type)),
+        )),
+        decimal_array(rows.iter().map(|r| r.last_trade_price))?,
+        Arc::new(UInt64Array::from_iter_values(rows.iter().map(|r| r.orders))),
+        decimal_array(rows.iter().map(|r| r.price))?,
+        decimal_array(rows.iter().map(|r| r.quantity))?,
+        Arc::new(UInt8Array::from_iter_values(
+            rows.iter().map(|r| u8::from(r.side)),
+        )),
+    ];
+    write_parquet(writer, l2_schema(), columns)
+}
+
+fn write_parquet<W: Write + Send>(
+    writer: W,
+    schema: SchemaRef,
+    columns: Vec<ArrayRef>,
+) -> Result<usize, NdaxError> {
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(writer, schema, Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(batch.num_rows())
+}
+
+fn symbol_of(catalogue: &InstrumentCatalogue, instrument_id: u64) -> Option<String> {
+    catalogue
+        .get(instrument_id)
+        .map(|instrument| instrument.symbol.clone())
+}
+
+fn decimal_type() -> DataType {
+    DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE)
+}
+
+fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Millisecond, Some(UTC.into()))
+}
+
+// Decimals as integer multiples of 10^-8, rounding anything finer
+fn decimal_array<I: Iterator<Item = Decimal>>(values: I) -> Result<ArrayRef, NdaxError> {
+    let units = values.map(|value| {
+        let mut value = value;
+        value.rescale(DECIMAL_SCALE as u32);
+        value.mantissa()
+    });
+    let array = Decimal128Array::from_iter_values(units)
+        .with_precision_and_scale(DECIMAL_PRECISION, DECIMAL_SCALE)?;
+    Ok(Arc::new(array))
+}
+
+// Unix milliseconds as UTC timestamps
+fn timestamp_array<I: Iterator<Item = u64>>(values: I) -> ArrayRef {
+    let array = TimestampMillisecondArray::from_iter_values(values.map(|millis| millis as i64))
+        .with_timezone(UTC);
+    Arc::new(array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{catalogue, temp_path, trade};
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use serde_json::json;
+
+    #[test]
+    fn test_writes_typed_trade_columns() {
+        let trades = vec![
+            trade(6913253, 1, 1718007168597),
+            trade(6913254, 2, 1718007169000),
+        ];
+        let path = temp_path("trades.parquet");
+        let written = export_trades(&path, ExportFormat::Parquet, &trades, &catalogue()).unwrap();
+        assert_eq!(written, 2);
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        let batch = &batches[0];
+        assert_eq!(batch.schema().field(7).data_type(), &timestamp_type());
+
+        let symbols = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(symbols.value(0), "BTCCAD");
+        // Instruments missing from the catalogue have no symbol
+        assert!(symbols.is_null(1));
+
+        let quantities = batch
+            .column(3)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        assert_eq!(quantities.value_as_string(0), "0.00749800");
+        let times = batch
+            .column(7)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(times.value(1), 1718007169000);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_flattens_l2_records() {
+        let entries = serde_json::from_value(json!([
+            [262, 1, 1718007168597u64, 0, 5711.7, 2, 5708.2, 1, 0.5, 0],
+            [263, 1, 1718007168597u64, 0, 5711.7, 1, 5712.0, 1, 0.25, 1]
+        ]))
+        .unwrap();
+        let records = vec![L2Record::new(
+            1718007168600,
+            L2RecordKind::Snapshot,
+            1,
+            entries,
+        )];
+        let rows = l2_rows(&records, &catalogue());
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].symbol.as_deref(), Some("BTCCAD"));
+        assert_eq!(rows[1].side, Side::Sell);
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &rows).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("ReceivedAt,Kind,InstrumentId,Symbol,MDUpdateId,"));
+
+        let mut parquet = Vec::new();
+        assert_eq!(write_l2_parquet(&mut parquet, &rows).unwrap(), 2);
+        assert!(parquet.starts_with(b"PAR1"));
+    }
+
+    #[test]
+    fn test_parses_format_names() {
+        assert_eq!(
+            "Parquet".parse::<ExportFormat>().unwrap(),
+            ExportFormat::Parquet
+        );
+        assert_eq!("csv".parse::<ExportFormat>().unwrap(), ExportFormat::Csv);
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use crate::account_session::AccountEvent;
+use crate::entities::position::Position;
+use crate::error::NdaxError;
+use crate::order_manager::OrderManager;
+
+/// The account's balances by product symbol, kept live from account events.
+///
+/// Load it with `refresh_with` on startup and after a reconnect, then feed it
+/// events with `apply_event`.
+#[derive(Debug, Default)]
+pub struct Portfolio {
+    positions: HashMap<String, Position>,
+}
+
+impl Portfolio {
+    pub fn new() -> Self {
+        Portfolio::default()
+    }
+
+    pub fn position(&self, product_symbol: &str) -> Option<&Position> {
+        self.positions.get(product_symbol)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values()
+    }
+
+    // Amount of a product free to trade, zero if the account holds none
+    pub fn available(&self, product_symbol: &str) -> Decimal {
+        self.position(product_symbol)
+            .map(Position::available)
+            .unwrap_or_default()
+    }
+
+    // Whether at least `amount` of the product is free, e.g. before sending an order
+    pub fn has_available(&self, product_symbol: &str, amount: Decimal) -> bool {
+        self.available(product_symbol) >= amount
+    }
+
+    // Replaces every balance with a full list, e.g. from GetAccountPositions
+    pub fn load(&mut self, positions: Vec<Position>) {
+        self.positions = positions
+            .into_iter()
+            .map(|position| (position.product_symbol.clone(), position))
+            .collect();
+    }
+
+    pub fn apply_position(&mut self, position: Position) {
+        self.positions
+            .insert(position.product_symbol.clone(), position);
+    }
+
+    // Applies an event from an AccountSession, returning the balance it changed
+    pub fn apply_event(&mut self, event: &AccountEvent) -> Option<&Position> {
+        match event {
+            AccountEvent::Position(position) => {
+                self.apply_position(position.clone());
+                self.positions.get(&position.product_symbol)
+            }
+            _ => None,
+        }
+    }
+
+    pub async fn refresh_with(&mut self, order_manager: &OrderManager) -> Result<(), NdaxError> {
+        self.load(order_manager.get_account_positions().await?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn position(product_symbol: &str, amount: &str, hold: &str) -> Position {
+        serde_json::from_value(serde_json::json!({
+            "AccountId": 185,
+            "ProductSymbol": product_symbol,
+            "ProductId": 1,
+            "Amount": amount,
+            "Hold": hold
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tracks_available_balance() {
+        let mut portfolio = Portfolio::new();
+        portfolio.load(vec![
+            position("BTC", "1.5", "0.25"),
+            position("CAD", "1000", "0"),
+        ]);
+        assert_eq!(portfolio.available("BTC"), dec("1.25"));
+        assert!(portfolio.has_available("CAD", dec("1000")));
+        assert_eq!(portfolio.available("ETH"), Decimal::ZERO);
+
+        let event = AccountEvent::Position(position("CAD", "1000", "600"));
+        let updated = portfolio.apply_event(&event).unwrap();
+        assert_eq!(updated.available(), dec("400"));
+        assert!(!portfolio.has_available("CAD", dec("500")));
+        assert!(portfolio.apply_event(&AccountEvent::Missed(3)).is_none());
+    }
+}
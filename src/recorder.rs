@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::constants;
+use crate::entities::level2_entry::Level2Entry;
+use crate::error::NdaxError;
+use crate::frame::Frame;
+use crate::order_book_registry::group_by_instrument;
+use crate::utc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum L2RecordKind {
+    // A full book from SubscribeLevel2 or GetL2Snapshot
+    Snapshot,
+    // Rows from a Level2UpdateEvent
+    Delta,
+}
+
+/// One line of a recorded Level2 file: the rows one frame carried for one instrument.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct L2Record {
+    // Local clock when the frame arrived, Unix milliseconds
+    pub received_at: u64,
+    pub kind: L2RecordKind,
+    pub instrument_id: u64,
+    // Highest MDUpdateId among the rows
+    pub md_update_id: u64,
+    pub entries: Vec<Level2Entry>,
+}
+
+impl L2Record {
+    pub fn new(
+        received_at: u64,
+        kind: L2RecordKind,
+        instrument_id: u64,
+        entries: Vec<Level2Entry>,
+    ) -> Self {
+        let md_update_id = entries
+            .iter()
+            .map(|entry| entry.md_update_id)
+            .max()
+            .unwrap_or_default();
+        L2Record {
+            received_at,
+            kind,
+            instrument_id,
+            md_update_id,
+            entries,
+        }
+    }
+}
+
+/// Writes every Level2 snapshot and delta it is given to per-instrument files.
+///
+/// Each instrument gets `l2_<InstrumentId>.jsonl` in the recorder's directory,
+/// holding one JSON `L2Record` per line in arrival order. Files are appended
+/// to, so a restarted recorder continues where it left off; the snapshot sent
+/// on resubscribe starts a new consistent stretch of the book.
+pub struct MarketDataRecorder {
+    directory: PathBuf,
+    writers: HashMap<u64, BufWriter<File>>,
+}
+
+impl MarketDataRecorder {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, NdaxError> {
+        fs::create_dir_all(&directory)?;
+        Ok(MarketDataRecorder {
+            directory: directory.as_ref().to_path_buf(),
+            writers: HashMap::new(),
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Records the Level2 rows of a gateway frame, stamped with the local time.
+    ///
+    /// Frames that carry no Level2 rows are ignored. Returns how many records
+    /// were written, one per instrument in the frame.
+    pub fn record_frame(&mut self, frame: &Frame) -> Result<usize, NdaxError> {
+        let kind = match frame.function_name.as_str() {
+            constants::SUBSCRIBE | constants::GET_L2_SNAPSHOT => L2RecordKind::Snapshot,
+            constants::UPDATE => L2RecordKind::Delta,
+            _ => return Ok(0),
+        };
+        let received_at = utc::now_millis();
+        let entries = frame.payload_as::<Vec<Level2Entry>>()?;
+
+        let mut written = 0;
+        for (instrument_id, entries) in group_by_instrument(entries) {
+            self.write(&L2Record::new(received_at, kind, instrument_id, entries))?;
+            written += 1;
+        }
+        self.flush()?;
+        Ok(written)
+    }
+
+    // Appends a record to its instrument's file; buffered until the next flush
+    pub fn write(&mut self, record: &L2Record) -> Result<(), NdaxError> {
+        let writer = match self.writers.get_mut(&record.instrument_id) {
+            Some(writer) => writer,
+            None => {
+                let path = l2_path(&self.directory, record.instrument_id);
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                self.writers
+                    .entry(record.instrument_id)
+                    .or_insert(BufWriter::new(file))
+            }
+        };
+        serde_json::to_writer(&mut *writer, record)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), NdaxError> {
+        for writer in self.writers.values_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MarketDataRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!("Error flushing recorded market data: {}", e);
+        }
+    }
+}
+
+// Where the Level2 records of an instrument are kept
+pub fn l2_path<P: AsRef<Path>>(directory: P, instrument_id: u64) -> PathBuf {
+    directory
+        .as_ref()
+        .join(format!("l2_{}.jsonl", instrument_id))
+}
+
+// Reads back every record of a Level2 file, in the order they were written
+pub fn read_l2_records<P: AsRef<Path>>(path: P) -> Result<Vec<L2Record>, NdaxError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(&line)?);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::OrderBook;
+    use crate::order_book_registry::OrderBookRegistry;
+    use serde_json::json;
+
+    fn to_frame(value: serde_json::Value) -> Frame {
+        Frame::decode(&value.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_records_snapshots_and_deltas_per_instrument() {
+        let directory = std::env::temp_dir().join(format!("ndax_recorder_{}", std::process::id()));
+        let mut recorder = MarketDataRecorder::new(&directory).unwrap();
+
+        let snapshot = to_frame(json!({
+            "i": 1, "m": 1, "n": "SubscribeLevel2",
+            "o": "[[1,1,1718003785385,0,5711.70000,1,5711.80000,1,8.13439401,1],
+                  [2,1,1718003785385,0,1.36000000,1,1.35990000,90,2500.00000000,0]]"
+        }));
+        let delta = to_frame(json!({
+            "i": 2, "m": 3, "n": "Level2UpdateEvent",
+            "o": "[[3,1,1718007168597,0,5711.70000,1,5711.75000,1,1.00000000,0]]"
+        }));
+        let trades = to_frame(json!({ "i": 3, "m": 3, "n": "TradeDataUpdateEvent", "o": "[]" }));
+        assert_eq!(recorder.record_frame(&snapshot).unwrap(), 2);
+        assert_eq!(recorder.record_frame(&delta).unwrap(), 1);
+        assert_eq!(recorder.record_frame(&trades).unwrap(), 0);
+
+        let records = read_l2_records(l2_path(&directory, 1)).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].kind, L2RecordKind::Snapshot);
+        assert_eq!(records[1].kind, L2RecordKind::Delta);
+        assert_eq!(records[1].md_update_id, 3);
+        assert!(records[1].received_at >= records[0].received_at);
+        assert_eq!(read_l2_records(l2_path(&directory, 90)).unwrap().len(), 1);
+        fs::remove_dir_all(&directory).unwrap();
+
+        // The recorded rows rebuild the same book offline
+        let mut book = OrderBook::new(10);
+        book.apply_snapshot(records[0].entries.clone());
+        book.apply_updates(records[1].entries.clone());
+        let registry = OrderBookRegistry::new(10);
+        registry.add_instrument(1);
+        registry.handle_frame(&snapshot);
+        registry.handle_frame(&delta);
+        let live = registry.snapshot(1).unwrap();
+        assert_eq!(book.bids(), live.bids());
+        assert_eq!(book.asks(), live.asks());
+    }
+}
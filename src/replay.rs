@@ -0,0 +1,281 @@
+use csv::ReaderBuilder;
+use std::path::Path;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration, Instant};
+
+use crate::constants;
+use crate::entities::trade_event::TradeEvent;
+use crate::error::NdaxError;
+use crate::frame::{Frame, MessageType};
+use crate::recorder::{read_l2_records, L2Record, L2RecordKind};
+use crate::trade_sink::read_trades;
+
+// Frames buffered between the replay task and its consumer
+const REPLAY_CHANNEL_CAPACITY: usize = 1024;
+
+/// How fast recorded frames are played back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    // With the gaps between frames as recorded
+    Original,
+    // With the gaps divided by this factor, e.g. 10.0 for ten times faster
+    Accelerated(f64),
+    // Back to back, only held up by the consumer
+    AsFastAsPossible,
+}
+
+impl ReplaySpeed {
+    // How long after the start of playback a frame recorded `elapsed` after the first one is due
+    fn due_after(&self, elapsed: Duration) -> Option<Duration> {
+        match self {
+            ReplaySpeed::Original => Some(elapsed),
+            // A tiny factor stretches the gap past what a Duration holds, so it saturates
+            ReplaySpeed::Accelerated(factor) if *factor > 0.0 => Some(
+                Duration::try_from_secs_f64(elapsed.as_secs_f64() / factor)
+                    .unwrap_or(Duration::MAX),
+            ),
+            ReplaySpeed::Accelerated(_) | ReplaySpeed::AsFastAsPossible => None,
+        }
+    }
+}
+
+/// Plays recorded market data back as the gateway frames it came from.
+///
+/// Level2 records become SubscribeLevel2 snapshots and Level2UpdateEvent
+/// deltas, trades become TradeDataUpdateEvent frames, so anything written
+/// against `WsClient::events()` can run on recorded data. Frames are played in
+/// timestamp order: the local receive time for Level2 records and the exchange
+/// trade time for trades. Ties keep the order they were added in.
+///
+/// Trade files carry no receive time, so the two clocks differ by network
+/// latency and any clock skew: trades keep their order among themselves, but
+/// how they interleave with Level2 frames is only approximate.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    speed: ReplaySpeed,
+    items: Vec<(u64, Frame)>,
+}
+
+impl Replay {
+    pub fn new(speed: ReplaySpeed) -> Self {
+        Replay {
+            speed,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn add_l2_records(&mut self, records: Vec<L2Record>) {
+        for record in records {
+            let (message_type, function_name) = match record.kind {
+                L2RecordKind::Snapshot => (MessageType::Reply, constants::SUBSCRIBE),
+                L2RecordKind::Delta => (MessageType::Event, constants::UPDATE),
+            };
+            let payload = record.entries.iter().map(|entry| entry.to_row()).collect();
+            let frame = Frame::new(message_type, 0, function_name, payload);
+            self.items.push((record.received_at, frame));
+        }
+    }
+
+    // Adds a file written by MarketDataRecorder, returning how many records it held
+    pub fn add_l2_file<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, NdaxError> {
+        let records = read_l2_records(path)?;
+        let count = records.len();
+        self.add_l2_records(records);
+        Ok(count)
+    }
+
+    pub fn add_trades(&mut self, trades: Vec<TradeEvent>) {
+        for trade in trades {
+            let frame = Frame::new(
+                MessageType::Event,
+                0,
+                constants::UPDATE_TRADES,
+                vec![trade.to_row()].into(),
+            );
+            self.items.push((trade.timestamp, frame));
+        }
+    }
+
+    // Adds a header-less trades CSV as written by csv_export::append_to_csv
+    pub fn add_trades_csv<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, NdaxError> {
+        let mut reader = ReaderBuilder::new().has_headers(false).from_path(path)?;
+        let trades = reader
+            .deserialize()
+            .collect::<Result<Vec<TradeEvent>, csv::Error>>()?;
+        let count = trades.len();
+        self.add_trades(trades);
+        Ok(count)
+    }
+
+    // Adds a day file written by TradeSink, compressed or not
+    pub fn add_trade_archive<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, NdaxError> {
+        let trades = read_trades(path)?;
+        let count = trades.len();
+        self.add_trades(trades);
+        Ok(count)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Every frame in playback order, without any pacing
+    pub fn frames(&self) -> Vec<Frame> {
+        self.sorted().into_iter().map(|(_, frame)| frame).collect()
+    }
+
+    // Plays the frames on a background task; the receiver ends after the last one
+    pub fn start(self) -> mpsc::Receiver<Frame> {
+        let (sender, receiver) = mpsc::channel(REPLAY_CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            self.run(sender).await;
+        });
+        receiver
+    }
+
+    /// Sends every frame to `sender`, paced according to the replay speed.
+    ///
+    /// Returns how many frames were delivered before the receiver went away.
+    pub async fn run(self, sender: mpsc::Sender<Frame>) -> usize {
+        let items = self.sorted();
+        let Some(&(first_timestamp, _)) = items.first() else {
+            return 0;
+        };
+        let started = Instant::now();
+
+        let mut delivered = 0;
+        for (timestamp, frame) in items {
+            let elapsed = Duration::from_millis(timestamp.saturating_sub(first_timestamp));
+            if let Some(due_after) = self.speed.due_after(elapsed) {
+                // sleep copes with a saturated gap, where adding it to an Instant would panic
+                sleep(due_after.saturating_sub(started.elapsed())).await;
+            }
+            if sender.send(frame).await.is_err() {
+                break;
+            }
+            delivered += 1;
+        }
+        delivered
+    }
+
+    // Frames ordered by timestamp, numbered like gateway frames
+    fn sorted(&self) -> Vec<(u64, Frame)> {
+        let mut items = self.items.clone();
+        items.sort_by_key(|(timestamp, _)| *timestamp);
+        for (sequence, (_, frame)) in items.iter_mut().enumerate() {
+            frame.sequence = sequence as u64;
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_export::append_to_csv;
+    use crate::entities::level2_entry::Level2Entry;
+    use crate::order_book_registry::OrderBookRegistry;
+    use crate::test_support::{temp_path, trade};
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    fn rows(value: serde_json::Value) -> Vec<Level2Entry> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn records() -> Vec<L2Record> {
+        vec![
+            L2Record::new(
+                1000,
+                L2RecordKind::Snapshot,
+                1,
+                rows(json!([
+                    [
+                        1,
+                        1,
+                        1718003785385u64,
+                        0,
+                        5711.7,
+                        1,
+                        5711.8,
+                        1,
+                        8.13439401,
+                        1
+                    ],
+                    [2, 1, 1718003785385u64, 0, 5711.7, 1, 5711.7, 1, 0.007498, 0]
+                ])),
+            ),
+            L2Record::new(
+                1250,
+                L2RecordKind::Delta,
+                1,
+                rows(json!([
+                    [3, 1, 1718007168597u64, 2, 5711.7, 0, 5711.7, 1, 0, 0],
+                    [4, 1, 1718007168597u64, 0, 5711.7, 1, 5711.6, 1, 1.5, 0]
+                ])),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_speed_scales_gaps() {
+        let gap = Duration::from_secs(10);
+        assert_eq!(ReplaySpeed::Original.due_after(gap), Some(gap));
+        assert_eq!(
+            ReplaySpeed::Accelerated(4.0).due_after(gap),
+            Some(Duration::from_millis(2500))
+        );
+        assert_eq!(ReplaySpeed::AsFastAsPossible.due_after(gap), None);
+        assert_eq!(
+            ReplaySpeed::Accelerated(f64::MIN_POSITIVE).due_after(gap),
+            Some(Duration::MAX)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_rebuilds_book() {
+        let trades_path = temp_path("replay_trades.csv");
+        let trade = trade(213, 1, 1100);
+        append_to_csv(&trades_path, &trade).unwrap();
+
+        let mut replay = Replay::new(ReplaySpeed::AsFastAsPossible);
+        replay.add_l2_records(records());
+        assert_eq!(replay.add_trades_csv(&trades_path).unwrap(), 1);
+        std::fs::remove_file(&trades_path).unwrap();
+
+        let names: Vec<String> = replay
+            .frames()
+            .into_iter()
+            .map(|frame| frame.function_name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "SubscribeLevel2",
+                "TradeDataUpdateEvent",
+                "Level2UpdateEvent"
+            ]
+        );
+
+        let registry = OrderBookRegistry::new(10);
+        registry.add_instrument(1);
+        let mut frames = replay.start();
+        let mut replayed_trades = Vec::new();
+        while let Some(frame) = frames.recv().await {
+            assert!(registry.handle_frame(&frame).is_empty());
+            if frame.function_name == constants::UPDATE_TRADES {
+                replayed_trades.extend(frame.payload_as::<Vec<TradeEvent>>().unwrap());
+            }
+        }
+
+        assert_eq!(replayed_trades, vec![trade]);
+        let book = registry.snapshot(1).unwrap();
+        assert_eq!(book.last_update_id(), Some(4));
+        assert_eq!(book.best_bid().unwrap().price(), Decimal::new(57116, 1));
+        assert_eq!(book.best_ask().unwrap().price(), Decimal::new(57118, 1));
+    }
+}
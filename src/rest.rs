@@ -0,0 +1,82 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+use crate::entities::generic_response::GenericResponse;
+use crate::error::NdaxError;
+
+// Checks the HTTP status of a REST response and decodes its JSON body
+pub async fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T, NdaxError> {
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs);
+        return Err(NdaxError::RateLimited { retry_after });
+    }
+
+    let body = response.text().await?;
+    // 401 means the session itself was refused, 403 that it lacks permission
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(NdaxError::SessionRejected(body));
+    }
+    if status == StatusCode::FORBIDDEN {
+        return Err(NdaxError::Authentication(body));
+    }
+    if !status.is_success() {
+        return Err(NdaxError::HttpStatus {
+            status: status.as_u16(),
+            body,
+        });
+    }
+    decode_body(&body)
+}
+
+// Decodes a JSON body, surfacing NDAX's own error reply when it doesn't match T
+pub fn decode_body<T: DeserializeOwned>(body: &str) -> Result<T, NdaxError> {
+    match serde_json::from_str::<T>(body) {
+        Ok(decoded) => Ok(decoded),
+        Err(e) => match serde_json::from_str::<GenericResponse>(body) {
+            Ok(response) if !response.result => Err(response.into()),
+            _ => Err(e.into()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::open_order::OpenOrder;
+    use tokio_tungstenite::tungstenite::http;
+
+    #[test]
+    fn test_decode_body_surfaces_exchange_error() {
+        let body = r#"{"result":false,"errormsg":"Not Authorized","errorcode":20,"detail":null}"#;
+        let result = decode_body::<Vec<OpenOrder>>(body);
+        assert!(matches!(result, Err(NdaxError::Authentication(_))));
+
+        let result = decode_body::<Vec<OpenOrder>>("<html>");
+        assert!(matches!(result, Err(NdaxError::Json(_))));
+    }
+
+    fn response(status: u16, body: &str) -> Response {
+        http::Response::builder()
+            .status(status)
+            .body(body.to_string())
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn test_separates_rejected_sessions_from_missing_permissions() {
+        let result = parse_response::<Vec<OpenOrder>>(response(401, "expired")).await;
+        assert!(matches!(result, Err(NdaxError::SessionRejected(_))));
+
+        let result = parse_response::<Vec<OpenOrder>>(response(403, "forbidden")).await;
+        assert!(matches!(result, Err(NdaxError::Authentication(_))));
+    }
+}
@@ -0,0 +1,624 @@
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use rust_decimal::Decimal;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::entities::order_trade_event::OrderTradeEvent;
+use crate::entities::order_types::{ClientOrderId, OrderId};
+use crate::entities::position::Position;
+use crate::entities::trade_event::TradeEvent;
+use crate::error::NdaxError;
+use crate::order_tracker::{OrderState, TrackedOrder};
+
+// Schema changes, applied in order; the database's user_version counts how
+// many have run. Append new migrations, never edit old ones.
+const MIGRATIONS: &[&str] = &[r#"
+    CREATE TABLE trades (
+        trade_id INTEGER PRIMARY KEY,
+        instrument_id INTEGER NOT NULL,
+        quantity TEXT NOT NULL,
+        price TEXT NOT NULL,
+        order_id_1 INTEGER NOT NULL,
+        order_id_2 INTEGER NOT NULL,
+        trade_time INTEGER NOT NULL,
+        side INTEGER NOT NULL,
+        taker_side INTEGER NOT NULL,
+        is_block_trade INTEGER NOT NULL,
+        client_id INTEGER NOT NULL
+    );
+    CREATE INDEX trades_by_instrument_time ON trades (instrument_id, trade_time);
+
+    CREATE TABLE orders (
+        order_id INTEGER PRIMARY KEY,
+        client_order_id INTEGER NOT NULL,
+        instrument_id INTEGER NOT NULL,
+        side TEXT NOT NULL,
+        price TEXT NOT NULL,
+        quantity TEXT NOT NULL,
+        filled_quantity TEXT NOT NULL,
+        avg_price TEXT NOT NULL,
+        state TEXT NOT NULL,
+        order_revision INTEGER NOT NULL,
+        last_updated_time INTEGER NOT NULL,
+        reject_reason TEXT,
+        cancel_reject_reason TEXT
+    );
+    CREATE INDEX orders_by_instrument_time ON orders (instrument_id, last_updated_time);
+    CREATE INDEX orders_by_client_order_id ON orders (client_order_id);
+
+    CREATE TABLE fills (
+        trade_id INTEGER PRIMARY KEY,
+        order_id INTEGER NOT NULL,
+        client_order_id INTEGER NOT NULL,
+        account_id INTEGER NOT NULL,
+        instrument_id INTEGER NOT NULL,
+        side TEXT NOT NULL,
+        quantity TEXT NOT NULL,
+        remaining_quantity TEXT NOT NULL,
+        price TEXT NOT NULL,
+        value TEXT NOT NULL,
+        fee TEXT NOT NULL,
+        fee_product_id INTEGER NOT NULL,
+        trade_time INTEGER NOT NULL
+    );
+    CREATE INDEX fills_by_order ON fills (order_id);
+    CREATE INDEX fills_by_instrument_time ON fills (instrument_id, trade_time);
+
+    CREATE TABLE positions (
+        oms_id INTEGER NOT NULL,
+        account_id INTEGER NOT NULL,
+        product_id INTEGER NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        product_symbol TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        hold TEXT NOT NULL,
+        pending_deposits TEXT NOT NULL,
+        pending_withdraws TEXT NOT NULL,
+        total_day_deposits TEXT NOT NULL,
+        total_day_withdraws TEXT NOT NULL,
+        PRIMARY KEY (account_id, product_id, recorded_at)
+    );
+"#];
+
+const TRADE_COLUMNS: &str = "trade_id, instrument_id, quantity, price, order_id_1, order_id_2, \
+     trade_time, side, taker_side, is_block_trade, client_id";
+const ORDER_COLUMNS: &str = "order_id, client_order_id, instrument_id, side, price, quantity, \
+     filled_quantity, avg_price, state, order_revision, last_updated_time, reject_reason, \
+     cancel_reject_reason";
+const POSITION_COLUMNS: &str = "oms_id, account_id, product_id, product_symbol, amount, hold, \
+     pending_deposits, pending_withdraws, total_day_deposits, total_day_withdraws";
+const ORDER_UPDATES: &str = "client_order_id = excluded.client_order_id, \
+     instrument_id = excluded.instrument_id, side = excluded.side, price = excluded.price, \
+     quantity = excluded.quantity, filled_quantity = excluded.filled_quantity, \
+     avg_price = excluded.avg_price, state = excluded.state, \
+     order_revision = excluded.order_revision, last_updated_time = excluded.last_updated_time, \
+     reject_reason = excluded.reject_reason, cancel_reject_reason = excluded.cancel_reject_reason";
+const FILL_COLUMNS: &str = "trade_id, order_id, client_order_id, account_id, instrument_id, side, \
+     quantity, remaining_quantity, price, value, fee, fee_product_id, trade_time";
+
+/// Local SQLite database of public trades, the account's orders, fills and positions.
+///
+/// Opening a database brings its schema up to date. Decimals are stored as
+/// text so prices and quantities keep every digit; times are Unix
+/// milliseconds, and a time range may end at `u64::MAX` to leave it open.
+/// Trades and fills are keyed by TradeId, so storing the same one twice is
+/// harmless.
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, NdaxError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, NdaxError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut connection: Connection) -> Result<Self, NdaxError> {
+        migrate(&mut connection)?;
+        Ok(Store { connection })
+    }
+
+    // The connection, for reports that need their own SQL
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    // Stores public trades, skipping ones already stored; returns how many were new
+    pub fn insert_trades(&mut self, trades: &[TradeEvent]) -> Result<usize, NdaxError> {
+        let transaction = self.connection.transaction()?;
+        let mut inserted = 0;
+        {
+            let mut statement = transaction.prepare_cached(&format!(
+                "INSERT OR IGNORE INTO trades ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                TRADE_COLUMNS
+            ))?;
+            for trade in trades {
+                inserted += statement.execute(params![
+                    trade.trade_id,
+                    trade.instrument_id,
+                    trade.quantity.to_string(),
+                    trade.price.to_string(),
+                    trade.order_id_1,
+                    trade.order_id_2,
+                    trade.timestamp,
+                    trade.side,
+                    trade.taker_side,
+                    trade.is_block_trade,
+                    trade.client_id,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(inserted)
+    }
+
+    // Public trades of an instrument with from <= TradeTime < to, oldest first
+    pub fn trades(
+        &self,
+        instrument_id: u64,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<TradeEvent>, NdaxError> {
+        let mut statement = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM trades WHERE instrument_id = ?1 AND trade_time >= ?2 AND trade_time < ?3 \
+             ORDER BY trade_time, trade_id",
+            TRADE_COLUMNS
+        ))?;
+        let trades = statement
+            .query_map(
+                params![instrument_id, sql_time(from), sql_time(to)],
+                trade_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(trades)
+    }
+
+    /// Stores the state of each order, e.g. from `OrderTracker::orders`.
+    ///
+    /// A stored order is only overwritten by the same or a later OrderRevision,
+    /// so a stale state written late cannot roll it back. Returns how many
+    /// orders were written.
+    pub fn upsert_orders<'a, I>(&mut self, orders: I) -> Result<usize, NdaxError>
+    where
+        I: IntoIterator<Item = &'a TrackedOrder>,
+    {
+        let transaction = self.connection.transaction()?;
+        let mut written = 0;
+        {
+            let mut statement = transaction.prepare_cached(&format!(
+                "INSERT INTO orders ({}) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13) \
+                 ON CONFLICT (order_id) DO UPDATE SET {} \
+                 WHERE excluded.order_revision >= orders.order_revision",
+                ORDER_COLUMNS, ORDER_UPDATES
+            ))?;
+            for order in orders {
+                written += statement.execute(params![
+                    order.order_id.0,
+                    order.client_order_id.0,
+                    order.instrument_id,
+                    order.side,
+                    order.price.to_string(),
+                    order.quantity.to_string(),
+                    order.filled_quantity.to_string(),
+                    order.avg_price.to_string(),
+                    state_name(order.state),
+                    order.order_revision,
+                    order.last_updated_time,
+                    order.reject_reason,
+                    order.cancel_reject_reason,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(written)
+    }
+
+    pub fn order(&self, order_id: OrderId) -> Result<Option<TrackedOrder>, NdaxError> {
+        let mut statement = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM orders WHERE order_id = ?1",
+            ORDER_COLUMNS
+        ))?;
+        let order = statement
+            .query_row(params![order_id.0], order_from_row)
+            .optional()?;
+        Ok(order)
+    }
+
+    // Orders of an instrument last updated with from <= LastUpdatedTime < to
+    pub fn orders(
+        &self,
+        instrument_id: u64,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<TrackedOrder>, NdaxError> {
+        let mut statement = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM orders WHERE instrument_id = ?1 \
+             AND last_updated_time >= ?2 AND last_updated_time < ?3 \
+             ORDER BY last_updated_time, order_id",
+            ORDER_COLUMNS
+        ))?;
+        let orders = statement
+            .query_map(
+                params![instrument_id, sql_time(from), sql_time(to)],
+                order_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(orders)
+    }
+
+    // Stores fills of the account's orders, skipping ones already stored
+    pub fn insert_fills(&mut self, fills: &[OrderTradeEvent]) -> Result<usize, NdaxError> {
+        let transaction = self.connection.transaction()?;
+        let mut inserted = 0;
+        {
+            let mut statement = transaction.prepare_cached(&format!(
+                "INSERT OR IGNORE INTO fills ({}) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                FILL_COLUMNS
+            ))?;
+            for fill in fills {
+                inserted += statement.execute(params![
+                    fill.trade_id,
+                    fill.order_id.0,
+                    fill.client_order_id,
+                    fill.account_id,
+                    fill.instrument_id,
+                    fill.side,
+                    fill.quantity.to_string(),
+                    fill.remaining_quantity.to_string(),
+                    fill.price.to_string(),
+                    fill.value.to_string(),
+                    fill.fee.to_string(),
+                    fill.fee_product_id,
+                    fill.trade_time_ms,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(inserted)
+    }
+
+    // Every fill of one order, oldest first
+    pub fn fills_for_order(&self, order_id: OrderId) -> Result<Vec<OrderTradeEvent>, NdaxError> {
+        let mut statement = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM fills WHERE order_id = ?1 ORDER BY trade_time, trade_id",
+            FILL_COLUMNS
+        ))?;
+        let fills = statement
+            .query_map(params![order_id.0], fill_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(fills)
+    }
+
+    // Fills on an instrument with from <= TradeTimeMS < to, oldest first
+    pub fn fills(
+        &self,
+        instrument_id: u64,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<OrderTradeEvent>, NdaxError> {
+        let mut statement = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM fills WHERE instrument_id = ?1 AND trade_time >= ?2 AND trade_time < ?3 \
+             ORDER BY trade_time, trade_id",
+            FILL_COLUMNS
+        ))?;
+        let fills = statement
+            .query_map(
+                params![instrument_id, sql_time(from), sql_time(to)],
+                fill_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(fills)
+    }
+
+    /// Records the account's balances as of `recorded_at`, in Unix milliseconds.
+    ///
+    /// Every call adds a snapshot rather than overwriting the last, so balances
+    /// can be compared between any two points in time.
+    pub fn record_positions(
+        &mut self,
+        positions: &[Position],
+        recorded_at: u64,
+    ) -> Result<usize, NdaxError> {
+        let transaction = self.connection.transaction()?;
+        let mut written = 0;
+        {
+            let mut statement = transaction.prepare_cached(&format!(
+                "INSERT OR REPLACE INTO positions ({}, recorded_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                POSITION_COLUMNS
+            ))?;
+            for position in positions {
+                written += statement.execute(params![
+                    position.oms_id,
+                    position.account_id,
+                    position.product_id,
+                    position.product_symbol,
+                    position.amount.to_string(),
+                    position.hold.to_string(),
+                    position.pending_deposits.to_string(),
+                    position.pending_withdraws.to_string(),
+                    position.total_day_deposits.to_string(),
+                    position.total_day_withdraws.to_string(),
+                    sql_time(recorded_at),
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(written)
+    }
+
+    // The latest balance of each product recorded at or before `at`
+    pub fn positions_at(&self, account_id: u64, at: u64) -> Result<Vec<Position>, NdaxError> {
+        let mut statement = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM positions p \
+             WHERE p.account_id = ?1 AND p.recorded_at = ( \
+                 SELECT MAX(recorded_at) FROM positions \
+                 WHERE account_id = p.account_id AND product_id = p.product_id \
+                 AND recorded_at <= ?2) \
+             ORDER BY p.product_id",
+            POSITION_COLUMNS
+        ))?;
+        let positions = statement
+            .query_map(params![account_id, sql_time(at)], position_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(positions)
+    }
+}
+
+// Runs the migrations this database has not seen yet, each in its own transaction
+fn migrate(connection: &mut Connection) -> Result<(), NdaxError> {
+    let version: usize = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", index + 1)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+fn trade_from_row(row: &Row) -> rusqlite::Result<TradeEvent> {
+    Ok(TradeEvent {
+        trade_id: row.get(0)?,
+        instrument_id: row.get(1)?,
+        quantity: decimal(row, 2)?,
+        price: decimal(row, 3)?,
+        order_id_1: row.get(4)?,
+        order_id_2: row.get(5)?,
+        timestamp: row.get(6)?,
+        side: row.get(7)?,
+        taker_side: row.get(8)?,
+        is_block_trade: row.get(9)?,
+        client_id: row.get(10)?,
+    })
+}
+
+fn order_from_row(row: &Row) -> rusqlite::Result<TrackedOrder> {
+    let state: String = row.get(8)?;
+    Ok(TrackedOrder {
+        order_id: OrderId(row.get(0)?),
+        client_order_id: ClientOrderId(row.get(1)?),
+        instrument_id: row.get(2)?,
+        side: row.get(3)?,
+        price: decimal(row, 4)?,
+        quantity: decimal(row, 5)?,
+        filled_quantity: decimal(row, 6)?,
+        avg_price: decimal(row, 7)?,
+        state: parse_state(&state).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                8,
+                Type::Text,
+                format!("unknown order state {}", state).into(),
+            )
+        })?,
+        order_revision: row.get(9)?,
+        last_updated_time: row.get(10)?,
+        reject_reason: row.get(11)?,
+        cancel_reject_reason: row.get(12)?,
+    })
+}
+
+fn position_from_row(row: &Row) -> rusqlite::Result<Position> {
+    Ok(Position {
+        oms_id: row.get(0)?,
+        account_id: row.get(1)?,
+        product_id: row.get(2)?,
+        product_symbol: row.get(3)?,
+        amount: decimal(row, 4)?,
+        hold: decimal(row, 5)?,
+        pending_deposits: decimal(row, 6)?,
+        pending_withdraws: decimal(row, 7)?,
+        total_day_deposits: decimal(row, 8)?,
+        total_day_withdraws: decimal(row, 9)?,
+    })
+}
+
+fn fill_from_row(row: &Row) -> rusqlite::Result<OrderTradeEvent> {
+    Ok(OrderTradeEvent {
+        oms_id: 1,
+        trade_id: row.get(0)?,
+        order_id: OrderId(row.get(1)?),
+        client_order_id: row.get(2)?,
+        account_id: row.get(3)?,
+        instrument_id: row.get(4)?,
+        side: row.get(5)?,
+        quantity: decimal(row, 6)?,
+        remaining_quantity: decimal(row, 7)?,
+        price: decimal(row, 8)?,
+        value: decimal(row, 9)?,
+        fee: decimal(row, 10)?,
+        fee_product_id: row.get(11)?,
+        trade_time_ms: row.get(12)?,
+    })
+}
+
+// SQLite integers are signed; bounds past i64::MAX already cover every stored
+// time, so they are clamped rather than refused
+fn sql_time(time: u64) -> i64 {
+    time.min(i64::MAX as u64) as i64
+}
+
+// Reads a decimal stored as text
+fn decimal(row: &Row, index: usize) -> rusqlite::Result<Decimal> {
+    let text: String = row.get(index)?;
+    Decimal::from_str(&text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+fn state_name(state: OrderState) -> &'static str {
+    match state {
+        OrderState::PendingNew => "PendingNew",
+        OrderState::Working => "Working",
+        OrderState::PartiallyFilled => "PartiallyFilled",
+        OrderState::Filled => "Filled",
+        OrderState::Canceled => "Canceled",
+        OrderState::Rejected => "Rejected",
+        OrderState::Expired => "Expired",
+    }
+}
+
+fn parse_state(name: &str) -> Option<OrderState> {
+    match name {
+        "PendingNew" => Some(OrderState::PendingNew),
+        "Working" => Some(OrderState::Working),
+        "PartiallyFilled" => Some(OrderState::PartiallyFilled),
+        "Filled" => Some(OrderState::Filled),
+        "Canceled" => Some(OrderState::Canceled),
+        "Rejected" => Some(OrderState::Rejected),
+        "Expired" => Some(OrderState::Expired),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_tracker::OrderTracker;
+    use crate::test_support::{temp_path, trade};
+    use serde_json::json;
+
+    fn fill(trade_id: u64, order_id: u64, trade_time: u64) -> OrderTradeEvent {
+        serde_json::from_value(json!({
+            "OMSId": 1, "TradeId": trade_id, "OrderId": order_id, "ClientOrderId": 42,
+            "AccountId": 185, "InstrumentId": 1, "Side": "Buy", "Quantity": 0.1,
+            "RemainingQuantity": 0.15, "Price": 5711.8, "Value": 571.18, "Fee": 0.0001,
+            "FeeProductId": 1, "TradeTimeMS": trade_time
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_migrations_are_idempotent() {
+        let path = temp_path("store.db");
+        Store::open(&path).unwrap();
+        let store = Store::open(&path).unwrap();
+        let version: usize = store
+            .connection()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_queries_trades_by_instrument_and_time() {
+        let mut store = Store::open_in_memory().unwrap();
+        let trades = vec![
+            trade(1, 1, 1718007168597),
+            trade(2, 90, 1718007168600),
+            trade(3, 1, 1718007169000),
+        ];
+        assert_eq!(store.insert_trades(&trades).unwrap(), 3);
+        // Replayed after a reconnect
+        assert_eq!(store.insert_trades(&trades[..1]).unwrap(), 0);
+
+        let found = store.trades(1, 1718007168597, 1718007169000).unwrap();
+        assert_eq!(found, vec![trades[0].clone()]);
+        // Decimals come back with every digit
+        assert_eq!(found[0].quantity, Decimal::new(749800, 8));
+    }
+
+    #[test]
+    fn test_stores_tracked_orders_and_fills() {
+        let mut tracker = OrderTracker::new();
+        tracker.reconcile(&[serde_json::from_value(json!({
+            "OMSId": 1, "OrderId": 6713, "ClientOrderId": 42, "Account": 185,
+            "Instrument": 1, "Side": "Buy", "OrderType": "Limit", "OrderState": "Working",
+            "Price": 5711.8, "Quantity": 0.25, "OrigQuantity": 0.25,
+            "QuantityExecuted": 0, "AvgPrice": 0,
+            "ReceiveTime": 1718007168597u64, "LastUpdatedTime": 1718007168600u64,
+            "OrderRevision": 1
+        }))
+        .unwrap()]);
+        tracker.apply_trade(&fill(213, 6713, 1718007169000));
+
+        let mut store = Store::open_in_memory().unwrap();
+        assert_eq!(store.upsert_orders(tracker.orders()).unwrap(), 1);
+        store
+            .insert_fills(&[fill(213, 6713, 1718007169000)])
+            .unwrap();
+
+        let order = store.order(OrderId(6713)).unwrap().unwrap();
+        assert_eq!(&order, tracker.get(OrderId(6713)).unwrap());
+        assert_eq!(order.state, OrderState::PartiallyFilled);
+        assert!(store.order(OrderId(1)).unwrap().is_none());
+        assert_eq!(store.orders(1, 0, u64::MAX).unwrap().len(), 1);
+
+        let fills = store.fills_for_order(OrderId(6713)).unwrap();
+        assert_eq!(fills, vec![fill(213, 6713, 1718007169000)]);
+    }
+
+    #[test]
+    fn test_keeps_the_latest_order_revision() {
+        let mut tracker = OrderTracker::new();
+        tracker.reconcile(&[serde_json::from_value(json!({
+            "OMSId": 1, "OrderId": 6713, "ClientOrderId": 42, "Account": 185,
+            "Instrument": 1, "Side": "Buy", "OrderType": "Limit", "OrderState": "Working",
+            "Price": 5711.8, "Quantity": 0.25, "OrigQuantity": 0.25,
+            "QuantityExecuted": 0, "AvgPrice": 0,
+            "ReceiveTime": 1718007168597u64, "LastUpdatedTime": 1718007168600u64,
+            "OrderRevision": 2
+        }))
+        .unwrap()]);
+        let latest = tracker.get(OrderId(6713)).unwrap().clone();
+        let mut stale = latest.clone();
+        stale.order_revision = 1;
+        stale.state = OrderState::PendingNew;
+
+        let mut store = Store::open_in_memory().unwrap();
+        assert_eq!(store.upsert_orders([&latest]).unwrap(), 1);
+        assert_eq!(store.upsert_orders([&stale]).unwrap(), 0);
+        assert_eq!(store.order(OrderId(6713)).unwrap().unwrap(), latest);
+    }
+
+    #[test]
+    fn test_positions_as_of_a_time() {
+        let position = |amount: i64| -> Position {
+            serde_json::from_value(json!({
+                "OMSId": 1, "AccountId": 185, "ProductSymbol": "BTC", "ProductId": 1,
+                "Amount": amount, "Hold": 0, "TotalDayDeposits": 0.5, "TotalDayWithdraws": 0.25
+            }))
+            .unwrap()
+        };
+        let mut store = Store::open_in_memory().unwrap();
+        store.record_positions(&[position(1)], 1000).unwrap();
+        store.record_positions(&[position(2)], 2000).unwrap();
+
+        assert_eq!(
+            store.positions_at(185, 1500).unwrap()[0].amount,
+            Decimal::ONE
+        );
+        assert_eq!(
+            store.positions_at(185, u64::MAX).unwrap(),
+            vec![position(2)]
+        );
+        assert!(store.positions_at(185, 500).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+//! Fixtures shared by the unit tests.
+
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::entities::instrument::Instrument;
+use crate::entities::trade_event::TradeEvent;
+use crate::instrument_catalogue::InstrumentCatalogue;
+
+// BTCCAD (1) and USDCCAD (90)
+pub(crate) fn catalogue() -> InstrumentCatalogue {
+    let instruments: Vec<Instrument> = serde_json::from_value(serde_json::json!([
+        {"InstrumentId": 1, "Symbol": "BTCCAD", "Product1": 1, "Product2": 3,
+         "PriceIncrement": 0.1, "QuantityIncrement": 0.00000001,
+         "MinimumQuantity": 0.0001, "SessionStatus": "Running"},
+        {"InstrumentId": 90, "Symbol": "USDCCAD", "Product1": 41, "Product2": 3,
+         "PriceIncrement": 0.0001, "QuantityIncrement": 0.01,
+         "MinimumQuantity": 1, "SessionStatus": "Running"}
+    ]))
+    .unwrap();
+    InstrumentCatalogue::new(instruments)
+}
+
+// A public trade of 0.00749800 at 5711.7, as the gateway sends it
+pub(crate) fn trade(trade_id: u64, instrument_id: u64, timestamp: u64) -> TradeEvent {
+    serde_json::from_value(serde_json::json!([
+        trade_id,
+        instrument_id,
+        0.00749800,
+        5711.7,
+        6713,
+        6714,
+        timestamp,
+        0,
+        1,
+        0,
+        0
+    ]))
+    .unwrap()
+}
+
+// A file or directory name under the temp directory, unique to this test run
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ndax_{}_{}", name, std::process::id()))
+}
+
+/// A REST server on localhost that answers each request with the next canned
+/// `(status, body)` reply, then stops.
+///
+/// Returns its base URL and the requests it received so far, each as the raw
+/// request line, headers and body.
+pub(crate) async fn canned_server(replies: Vec<(u16, Value)>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = Arc::clone(&requests);
+    tokio::spawn(async move {
+        for (status, body) in replies {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut stream).await;
+            received.lock().unwrap().push(request);
+            let body = body.to_string();
+            let response = format!(
+                "HTTP/1.1 {} Canned\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+    (base_url, requests)
+}
+
+// Reads one HTTP request, using Content-Length to find the end of its body
+async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+        let text = String::from_utf8_lossy(&request);
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let content_length = text[..header_end]
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if request.len() >= header_end + 4 + content_length || read == 0 {
+                return text.into_owned();
+            }
+        } else if read == 0 {
+            return text.into_owned();
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use csv::{ReaderBuilder, Writer, WriterBuilder};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use tokio::time::{Duration, Instant};
+
+use crate::entities::trade_event::TradeEvent;
+use crate::error::NdaxError;
+use crate::utc;
+
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+// Trade ids remembered per instrument, far more than NDAX replays on a resubscribe
+const DEFAULT_DEDUPE_WINDOW: usize = 10_000;
+// zstd's own default level
+const ZSTD_LEVEL: i32 = 0;
+const ARCHIVE_EXTENSIONS: [&str; 2] = ["gz", "zst"];
+
+/// How a day's file is compressed once the sink moves on to the next day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn extension(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gz"),
+            Compression::Zstd => Some("zst"),
+        }
+    }
+}
+
+// The open CSV file of one instrument for one UTC day
+struct DayFile {
+    day: u64,
+    path: PathBuf,
+    writer: Writer<BufWriter<File>>,
+}
+
+// The latest trade ids of one instrument, forgetting the oldest beyond the window
+#[derive(Default)]
+struct RecentTradeIds {
+    ids: HashSet<u64>,
+    order: VecDeque<u64>,
+}
+
+impl RecentTradeIds {
+    fn contains(&self, trade_id: u64) -> bool {
+        self.ids.contains(&trade_id)
+    }
+
+    fn insert(&mut self, trade_id: u64, window: usize) {
+        if !self.ids.insert(trade_id) {
+            return;
+        }
+        self.order.push_back(trade_id);
+        while self.order.len() > window {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Long-lived archive of public trades, one CSV file per instrument per UTC day.
+///
+/// Files are named `trades_<InstrumentId>_<YYYY-MM-DD>.csv` and start with a
+/// header row. Each trade goes to the file of its own trade day; when a
+/// later day begins the previous file is closed and, if asked, compressed,
+/// along with any earlier day a previous run left uncompressed.
+/// A compressed day is sealed: trades for it that are not already in it are
+/// dropped rather than written elsewhere.
+///
+/// The last `dedupe_window` trade ids of each instrument are remembered across
+/// days and seeded from existing files, so the trades NDAX repeats after a
+/// resubscribe or a restart are only stored once.
+///
+/// Writes are buffered. `write` flushes once `flush_interval` has passed, but
+/// a quiet instrument may not write again for a long time, so callers should
+/// also call `flush_if_due` or `flush` on a timer.
+pub struct TradeSink {
+    directory: PathBuf,
+    compression: Compression,
+    flush_interval: Duration,
+    dedupe_window: usize,
+    last_flush: Instant,
+    // The latest day of each instrument
+    files: HashMap<u64, DayFile>,
+    // Earlier days that late trades were filed under, by (InstrumentId, day)
+    earlier_files: HashMap<(u64, u64), DayFile>,
+    recent: HashMap<u64, RecentTradeIds>,
+    // (InstrumentId, day) pairs whose files on disk were read into `recent`
+    loaded_days: HashSet<(u64, u64)>,
+}
+
+impl TradeSink {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, NdaxError> {
+        fs::create_dir_all(&directory)?;
+        Ok(TradeSink {
+            directory: directory.as_ref().to_path_buf(),
+            compression: Compression::default(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            dedupe_window: DEFAULT_DEDUPE_WINDOW,
+            last_flush: Instant::now(),
+            files: HashMap::new(),
+            earlier_files: HashMap::new(),
+            recent: HashMap::new(),
+            loaded_days: HashSet::new(),
+        })
+    }
+
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    pub fn with_dedupe_window(mut self, dedupe_window: usize) -> Self {
+        self.dedupe_window = dedupe_window;
+        self
+    }
+
+    /// Archives one trade, returning false if it was not written.
+    ///
+    /// That is the case for a trade already stored, and for one whose day has
+    /// already been compressed.
+    pub fn write(&mut self, trade: &TradeEvent) -> Result<bool, NdaxError> {
+        let instrument_id = trade.instrument_id;
+        let day = utc::day_number(trade.timestamp);
+        self.load_day(instrument_id, trade.timestamp)?;
+        if self
+            .recent
+            .get(&instrument_id)
+            .is_some_and(|recent| recent.contains(trade.trade_id))
+        {
+            return Ok(false);
+        }
+
+        let open_day = self.files.get(&instrument_id).map(|file| file.day);
+        let written = match open_day {
+            Some(open_day) if day == open_day => {
+                let file = self
+                    .files
+                    .get_mut(&instrument_id)
+                    .expect("day file is open");
+                file.writer.serialize(trade)?;
+                true
+            }
+            Some(open_day) if day < open_day => self.write_earlier_day(trade)?,
+            _ if self.archive_of(instrument_id, trade.timestamp).is_some() => {
+                self.write_earlier_day(trade)?
+            }
+            _ => {
+                if let Some(previous) = self.files.remove(&instrument_id) {
+                    self.close(previous)?;
+                }
+                self.close_earlier_days(instrument_id)?;
+                self.compress_leftovers(instrument_id, trade.timestamp)?;
+                let path = self.path_for(instrument_id, trade.timestamp);
+                let mut writer = open_csv(&path)?;
+                writer.serialize(trade)?;
+                self.files
+                    .insert(instrument_id, DayFile { day, path, writer });
+                true
+            }
+        };
+
+        if written {
+            self.recent
+                .entry(instrument_id)
+                .or_default()
+                .insert(trade.trade_id, self.dedupe_window);
+        }
+        self.flush_if_due()?;
+        Ok(written)
+    }
+
+    // Archives a batch, e.g. one TradeDataUpdateEvent, returning how many were new
+    pub fn write_all(&mut self, trades: &[TradeEvent]) -> Result<usize, NdaxError> {
+        let mut written = 0;
+        for trade in trades {
+            if self.write(trade)? {
+                written += 1;
+            }
+        }
+        Ok(written)
+    }
+
+    // Flushes if `flush_interval` has passed since the last flush
+    pub fn flush_if_due(&mut self) -> Result<(), NdaxError> {
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), NdaxError> {
+        for file in self
+            .files
+            .values_mut()
+            .chain(self.earlier_files.values_mut())
+        {
+            file.writer.flush()?;
+        }
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+
+    // Where the trades of an instrument for the UTC day of `timestamp` are written
+    pub fn path_for(&self, instrument_id: u64, timestamp: u64) -> PathBuf {
+        self.directory.join(format!(
+            "trades_{}_{}.csv",
+            instrument_id,
+            utc::format_date(timestamp)
+        ))
+    }
+
+    // The compressed file of a day, if the day has been sealed
+    fn archive_of(&self, instrument_id: u64, timestamp: u64) -> Option<PathBuf> {
+        let path = self.path_for(instrument_id, timestamp);
+        ARCHIVE_EXTENSIONS
+            .iter()
+            .map(|extension| with_extension(&path, extension))
+            .find(|archive| archive.exists())
+    }
+
+    // Compresses the plain files of days before `timestamp`'s, e.g. the day a
+    // previous run was writing when it stopped
+    fn compress_leftovers(&self, instrument_id: u64, timestamp: u64) -> Result<(), NdaxError> {
+        let Some(extension) = self.compression.extension() else {
+            return Ok(());
+        };
+        let prefix = format!("trades_{}_", instrument_id);
+        let date = utc::format_date(timestamp);
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            let earlier = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(|name| name.strip_suffix(".csv"))
+                .is_some_and(|day| day < date.as_str());
+            let archived = ARCHIVE_EXTENSIONS
+                .iter()
+                .any(|archive| with_extension(&path, archive).exists());
+            if earlier && !archived {
+                compress(&path, self.compression, extension)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Remembers the trade ids already on disk for a day, reading each day once
+    fn load_day(&mut self, instrument_id: u64, timestamp: u64) -> Result<(), NdaxError> {
+        if !self
+            .loaded_days
+            .insert((instrument_id, utc::day_number(timestamp)))
+        {
+            return Ok(());
+        }
+        let path = self.path_for(instrument_id, timestamp);
+        let files = self
+            .archive_of(instrument_id, timestamp)
+            .into_iter()
+            .chain(Some(path).filter(|path| path.exists()));
+        for file in files {
+            let recent = self.recent.entry(instrument_id).or_default();
+            for trade in read_trades(file)? {
+                recent.insert(trade.trade_id, self.dedupe_window);
+            }
+        }
+        Ok(())
+    }
+
+    // Files a trade from before the open day, unless that day is already compressed
+    fn write_earlier_day(&mut self, trade: &TradeEvent) -> Result<bool, NdaxError> {
+        let day = utc::day_number(trade.timestamp);
+        let key = (trade.instrument_id, day);
+        if !self.earlier_files.contains_key(&key) {
+            if self
+                .archive_of(trade.instrument_id, trade.timestamp)
+                .is_some()
+            {
+                return Ok(false);
+            }
+            let path = self.path_for(trade.instrument_id, trade.timestamp);
+            let writer = open_csv(&path)?;
+            self.earlier_files
+                .insert(key, DayFile { day, path, writer });
+        }
+        let file = self
+            .earlier_files
+            .get_mut(&key)
+            .expect("earlier day file is open");
+        file.writer.serialize(trade)?;
+        Ok(true)
+    }
+
+    // Closes the earlier days of an instrument once it moves on to a new day
+    fn close_earlier_days(&mut self, instrument_id: u64) -> Result<(), NdaxError> {
+        let keys: Vec<(u64, u64)> = self
+            .earlier_files
+            .keys()
+            .filter(|(id, _)| *id == instrument_id)
+            .copied()
+            .collect();
+        for key in keys {
+            if let Some(file) = self.earlier_files.remove(&key) {
+                self.close(file)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Flushes a finished day's file and compresses it if configured
+    fn close(&self, mut file: DayFile) -> Result<(), NdaxError> {
+        file.writer.flush()?;
+        drop(file.writer);
+        if let Some(extension) = self.compression.extension() {
+            compress(&file.path, self.compression, extension)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TradeSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!("Error flushing trade archive: {}", e);
+        }
+    }
+}
+
+// Opens a CSV file for appending, writing the header only if the file is new
+fn open_csv(path: &Path) -> Result<Writer<BufWriter<File>>, NdaxError> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let has_header = file.metadata()?.len() > 0;
+    Ok(WriterBuilder::new()
+        .has_headers(!has_header)
+        .from_writer(BufWriter::new(file)))
+}
+
+fn with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// Replaces a file with its compressed copy, e.g. trades.csv with trades.csv.gz.
+///
+/// An existing compressed file is never overwritten: compressing fails and
+/// the plain file is kept.
+fn compress(path: &Path, compression: Compression, extension: &str) -> Result<PathBuf, NdaxError> {
+    let compressed_path = with_extension(path, extension);
+    let mut input = BufReader::new(File::open(path)?);
+    let output = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&compressed_path)?;
+    let output = BufWriter::new(output);
+    match compression {
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(output, flate2::Compression::default());
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?;
+        }
+        Compression::Zstd => zstd::stream::copy_encode(&mut input, output, ZSTD_LEVEL)?,
+        Compression::None => return Ok(path.to_path_buf()),
+    }
+    fs::remove_file(path)?;
+    Ok(compressed_path)
+}
+
+// Reads an archived day of trades, plain or compressed, judging by the extension
+pub fn read_trades<P: AsRef<Path>>(path: P) -> Result<Vec<TradeEvent>, NdaxError> {
+    let path = path.as_ref();
+    let file = BufReader::new(File::open(path)?);
+    let input: Box<dyn Read> = match path.extension().and_then(|e| e.to_str()) {
+        Some("gz") => Box::new(MultiGzDecoder::new(file)),
+        Some("zst") => Box::new(zstd::stream::read::Decoder::new(file)?),
+        _ => Box::new(file),
+    };
+    let mut reader = ReaderBuilder::new().has_headers(true).from_reader(input);
+    let trades = reader
+        .deserialize()
+        .collect::<Result<Vec<TradeEvent>, csv::Error>>()?;
+    Ok(trades)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{temp_path, trade};
+
+    // 2024-06-10T08:12:48.597Z and one day later
+    const DAY_ONE: u64 = 1718007168597;
+    const DAY_TWO: u64 = DAY_ONE + 86_400_000;
+
+    #[test]
+    fn test_splits_by_instrument_and_day() {
+        let directory = temp_path("sink_rotation");
+        let mut sink = TradeSink::new(&directory)
+            .unwrap()
+            .with_compression(Compression::Gzip);
+
+        assert!(sink.write(&trade(1, 1, DAY_ONE)).unwrap());
+        assert!(sink.write(&trade(2, 90, DAY_ONE)).unwrap());
+        // Repeated after a resubscribe
+        assert!(!sink.write(&trade(1, 1, DAY_ONE)).unwrap());
+        assert!(sink.write(&trade(3, 1, DAY_TWO)).unwrap());
+        sink.flush().unwrap();
+
+        let day_one = sink.path_for(1, DAY_ONE);
+        assert!(!day_one.exists());
+        let archived = read_trades(day_one.with_extension("csv.gz")).unwrap();
+        assert_eq!(archived, vec![trade(1, 1, DAY_ONE)]);
+        let day_two = sink.path_for(1, DAY_TWO);
+        assert_eq!(read_trades(&day_two).unwrap(), vec![trade(3, 1, DAY_TWO)]);
+        let usdc = fs::read_to_string(sink.path_for(90, DAY_ONE)).unwrap();
+        assert!(usdc.starts_with("TradeId,ProductPairCode,Quantity,Price,"));
+
+        drop(sink);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_resumes_existing_day_file() {
+        let directory = temp_path("sink_resume");
+        let mut sink = TradeSink::new(&directory).unwrap();
+        sink.write(&trade(1, 1, DAY_ONE)).unwrap();
+        drop(sink);
+
+        let mut sink = TradeSink::new(&directory).unwrap();
+        assert!(!sink.write(&trade(1, 1, DAY_ONE)).unwrap());
+        assert!(sink.write(&trade(2, 1, DAY_ONE)).unwrap());
+        sink.flush().unwrap();
+
+        let contents = fs::read_to_string(sink.path_for(1, DAY_ONE)).unwrap();
+        assert_eq!(contents.matches("TradeId").count(), 1);
+        assert_eq!(read_trades(sink.path_for(1, DAY_ONE)).unwrap().len(), 2);
+
+        drop(sink);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_zstd_round_trip() {
+        let directory = temp_path("sink_zstd");
+        let mut sink = TradeSink::new(&directory)
+            .unwrap()
+            .with_compression(Compression::Zstd);
+        sink.write(&trade(1, 1, DAY_ONE)).unwrap();
+        sink.write(&trade(2, 1, DAY_TWO)).unwrap();
+
+        let archived = sink.path_for(1, DAY_ONE).with_extension("csv.zst");
+        assert_eq!(read_trades(archived).unwrap(), vec![trade(1, 1, DAY_ONE)]);
+
+        drop(sink);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_restart_keeps_compressed_day() {
+        let directory = temp_path("sink_sealed");
+        let mut sink = TradeSink::new(&directory)
+            .unwrap()
+            .with_compression(Compression::Gzip);
+        sink.write(&trade(1, 1, DAY_ONE)).unwrap();
+        sink.write(&trade(2, 1, DAY_TWO)).unwrap();
+        drop(sink);
+
+        // IncludeLastCount replays day one's trade after the restart
+        let mut sink = TradeSink::new(&directory)
+            .unwrap()
+            .with_compression(Compression::Gzip);
+        assert!(!sink.write(&trade(1, 1, DAY_ONE)).unwrap());
+        // A day that is already compressed is not reopened
+        assert!(!sink.write(&trade(3, 1, DAY_ONE)).unwrap());
+        assert!(!sink.path_for(1, DAY_ONE).exists());
+        assert!(sink.write(&trade(4, 1, DAY_TWO)).unwrap());
+        sink.flush().unwrap();
+
+        let archived = sink.path_for(1, DAY_ONE).with_extension("csv.gz");
+        assert_eq!(read_trades(archived).unwrap(), vec![trade(1, 1, DAY_ONE)]);
+        assert_eq!(
+            read_trades(sink.path_for(1, DAY_TWO)).unwrap(),
+            vec![trade(2, 1, DAY_TWO), trade(4, 1, DAY_TWO)]
+        );
+
+        drop(sink);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_restart_after_midnight_compresses_previous_day() {
+        let directory = temp_path("sink_leftover");
+        let mut sink = TradeSink::new(&directory)
+            .unwrap()
+            .with_compression(Compression::Zstd);
+        sink.write(&trade(1, 1, DAY_ONE)).unwrap();
+        sink.write(&trade(2, 90, DAY_ONE)).unwrap();
+        drop(sink);
+
+        // Stopped before midnight, started again after it
+        let mut sink = TradeSink::new(&directory)
+            .unwrap()
+            .with_compression(Compression::Zstd);
+        assert!(sink.write(&trade(3, 1, DAY_TWO)).unwrap());
+
+        assert!(!sink.path_for(1, DAY_ONE).exists());
+        let archived = sink.path_for(1, DAY_ONE).with_extension("csv.zst");
+        assert_eq!(read_trades(archived).unwrap(), vec![trade(1, 1, DAY_ONE)]);
+        // Instrument 90 has not reached the new day yet
+        assert!(sink.path_for(90, DAY_ONE).exists());
+
+        drop(sink);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_files_late_trades_under_their_own_day() {
+        let directory = temp_path("sink_late");
+        let mut sink = TradeSink::new(&directory).unwrap();
+        sink.write(&trade(1, 1, DAY_ONE)).unwrap();
+        sink.write(&trade(2, 1, DAY_TWO)).unwrap();
+        // A resubscribe just after midnight repeats yesterday's trade
+        assert!(!sink.write(&trade(1, 1, DAY_ONE)).unwrap());
+        assert!(sink.write(&trade(3, 1, DAY_ONE + 1)).unwrap());
+        sink.flush().unwrap();
+
+        assert_eq!(
+            read_trades(sink.path_for(1, DAY_ONE)).unwrap(),
+            vec![trade(1, 1, DAY_ONE), trade(3, 1, DAY_ONE + 1)]
+        );
+        assert_eq!(
+            read_trades(sink.path_for(1, DAY_TWO)).unwrap(),
+            vec![trade(2, 1, DAY_TWO)]
+        );
+
+        drop(sink);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+//! UTC calendar helpers for NDAX timestamps, which are Unix times in seconds
+//! or milliseconds.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// The local clock as Unix milliseconds, e.g. to stamp when a frame arrived
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+// The date and time NDAX expects in FromDate / ToDate, e.g. "2024-06-10T08:12:48"
+pub fn format_iso8601(unix_seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((unix_seconds / SECONDS_PER_DAY) as i64);
+    let seconds_of_day = unix_seconds % SECONDS_PER_DAY;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+// The UTC calendar day of a millisecond timestamp, e.g. "2024-06-10"
+pub fn format_date(unix_millis: u64) -> String {
+    let (year, month, day) = civil_from_days((unix_millis / 1000 / SECONDS_PER_DAY) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Number of whole days since 1970-01-01 of a millisecond timestamp
+pub fn day_number(unix_millis: u64) -> u64 {
+    unix_millis / 1000 / SECONDS_PER_DAY
+}
+
+// Converts days since 1970-01-01 to a (year, month, day) date in the proleptic
+// Gregorian calendar, following Howard Hinnant's civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_utc_times() {
+        assert_eq!(format_iso8601(0), "1970-01-01T00:00:00");
+        assert_eq!(format_iso8601(1718007168), "2024-06-10T08:12:48");
+        assert_eq!(format_iso8601(951782400), "2000-02-29T00:00:00");
+        assert_eq!(format_date(1718007168597), "2024-06-10");
+        assert_eq!(day_number(86_399_999), 0);
+        assert_eq!(day_number(86_400_000), 1);
+    }
+}
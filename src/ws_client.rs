@@ -0,0 +1,646 @@
+use futures_util::{SinkExt, StreamExt};
+use rand::Rng;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+use tokio::time::{interval, sleep, timeout, Duration, Instant};
+use tokio_tungstenite::{
+    connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream,
+};
+use url::Url;
+
+use crate::auth::check_authenticated;
+use crate::constants;
+use crate::entities::authenticate_response::AuthenticateResponse;
+use crate::entities::generic_response::GenericResponse;
+use crate::error::NdaxError;
+use crate::frame::{Frame, MessageType};
+
+const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(10);
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+const STATUS_CHANNEL_CAPACITY: usize = 64;
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type Authenticator = Arc<dyn Fn() -> Value + Send + Sync>;
+type TwoFactorCode = Arc<dyn Fn() -> String + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connected,
+    Reconnecting,
+}
+
+/// Something the connection supervisor ran into, reported for the caller to log.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionEvent {
+    // The connection was lost, and why
+    Dropped(String),
+    // Waiting `delay` before reconnect attempt `attempt`
+    Reconnecting { attempt: u32, delay: Duration },
+    ReconnectFailed(String),
+    // Signing in again or replaying a subscription failed on a new connection
+    RestoreFailed(String),
+    // A text message that is not a gateway frame, which was skipped
+    UndecodableFrame(String),
+}
+
+impl fmt::Display for ConnectionEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConnectionEvent::Dropped(reason) => write!(f, "connection dropped: {}", reason),
+            ConnectionEvent::Reconnecting { attempt, delay } => {
+                write!(f, "reconnecting in {:?} (attempt {})", delay, attempt)
+            }
+            ConnectionEvent::ReconnectFailed(e) => write!(f, "error reconnecting: {}", e),
+            ConnectionEvent::RestoreFailed(e) => write!(f, "error restoring session: {}", e),
+            ConnectionEvent::UndecodableFrame(e) => write!(f, "error decoding frame: {}", e),
+        }
+    }
+}
+
+/// Controls how a dropped connection is detected and re-established.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    // How often a gateway Ping is sent to keep the connection alive
+    pub heartbeat_interval: Duration,
+    // How long the connection may stay silent before it is considered dead
+    pub heartbeat_timeout: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            heartbeat_interval: Duration::from_secs(15),
+            heartbeat_timeout: Duration::from_secs(45),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    // Exponential backoff for the given attempt, with jitter so clients don't reconnect in lockstep
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.min(16)));
+        let capped = exponential.min(self.max_backoff);
+        capped.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Subscription {
+    function_name: String,
+    payload: Value,
+}
+
+// Why a connection stopped being served
+#[derive(Debug, PartialEq)]
+enum Disconnect {
+    Dropped(String),
+    Shutdown,
+}
+
+// State shared between every client handle and the connection supervisor
+struct Shared {
+    next_sequence: AtomicU64,
+    pending: Mutex<HashMap<u64, oneshot::Sender<Frame>>>,
+    subscriptions: Mutex<Vec<Subscription>>,
+    authenticator: Mutex<Option<Authenticator>>,
+    two_factor_code: Mutex<Option<TwoFactorCode>>,
+    events: broadcast::Sender<Frame>,
+    state: watch::Sender<ConnectionState>,
+    status: broadcast::Sender<ConnectionEvent>,
+}
+
+impl Shared {
+    fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (state, _) = watch::channel(ConnectionState::Connected);
+        let (status, _) = broadcast::channel(STATUS_CHANNEL_CAPACITY);
+        Shared {
+            next_sequence: AtomicU64::new(1),
+            pending: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(Vec::new()),
+            authenticator: Mutex::new(None),
+            two_factor_code: Mutex::new(None),
+            events,
+            state,
+            status,
+        }
+    }
+
+    fn next_sequence(&self) -> u64 {
+        self.next_sequence.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn dispatch(&self, frame: Frame) {
+        if matches!(frame.message_type, MessageType::Reply | MessageType::Error) {
+            if let Some(reply_tx) = self.pending.lock().unwrap().remove(&frame.sequence) {
+                let _ = reply_tx.send(frame.clone());
+            }
+        }
+        // No subscribers is not an error, the frame is simply dropped
+        let _ = self.events.send(frame);
+    }
+
+    // Reports what the supervisor ran into; nobody listening is fine too
+    fn report(&self, event: ConnectionEvent) {
+        let _ = self.status.send(event);
+    }
+
+    fn record_subscription(&self, subscription: Subscription) {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if !subscriptions.contains(&subscription) {
+            subscriptions.push(subscription);
+        }
+    }
+
+    // The subscriptions to replay on a new connection: account events first, then
+    // the rest in the order they were made
+    fn replay_order(&self) -> Vec<Subscription> {
+        let mut subscriptions = self.subscriptions.lock().unwrap().clone();
+        subscriptions.sort_by_key(|s| s.function_name != constants::SUBSCRIBE_ACCOUNT_EVENTS);
+        subscriptions
+    }
+
+    // Forgets the subscriptions an Unsubscribe* call cancels
+    fn remove_subscription(&self, unsubscribe_name: &str, payload: &Value) {
+        let function_name = unsubscribe_name.replacen("Unsubscribe", "Subscribe", 1);
+        let instrument_id = payload.get("InstrumentId");
+        self.subscriptions.lock().unwrap().retain(|s| {
+            s.function_name != function_name || s.payload.get("InstrumentId") != instrument_id
+        });
+    }
+}
+
+/// Client for the NDAX WebSocket gateway.
+///
+/// Every outgoing request gets its own sequence number so that replies can be
+/// matched back to the caller. All received frames, replies included, are also
+/// published to event subscribers.
+///
+/// The connection is supervised: when it drops it is re-established with
+/// exponential backoff, the session is re-authenticated and every active
+/// subscription is replayed. What went wrong along the way is reported on
+/// `connection_events` rather than printed.
+#[derive(Clone)]
+pub struct WsClient {
+    outbound: mpsc::UnboundedSender<Message>,
+    shared: Arc<Shared>,
+    call_timeout: Duration,
+}
+
+impl WsClient {
+    pub async fn connect(url: &Url) -> Result<Self, NdaxError> {
+        Self::connect_with_policy(url, ReconnectPolicy::default()).await
+    }
+
+    pub async fn connect_with_policy(
+        url: &Url,
+        policy: ReconnectPolicy,
+    ) -> Result<Self, NdaxError> {
+        let (ws_stream, _response) = connect_async(url.clone()).await?;
+
+        let (outbound, outbound_rx) = mpsc::unbounded_channel::<Message>();
+        let client = WsClient {
+            outbound,
+            shared: Arc::new(Shared::new()),
+            call_timeout: DEFAULT_CALL_TIMEOUT,
+        };
+
+        tokio::spawn(supervise(
+            url.clone(),
+            policy,
+            ws_stream,
+            client.clone(),
+            outbound_rx,
+        ));
+
+        Ok(client)
+    }
+
+    pub fn with_call_timeout(mut self, call_timeout: Duration) -> Self {
+        self.call_timeout = call_timeout;
+        self
+    }
+
+    // Allocates the next request sequence number
+    pub fn next_sequence(&self) -> u64 {
+        self.shared.next_sequence()
+    }
+
+    // Receives every frame read from the gateway, across reconnects
+    pub fn events(&self) -> broadcast::Receiver<Frame> {
+        self.shared.events.subscribe()
+    }
+
+    // Watches the connection going down and coming back up
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.shared.state.subscribe()
+    }
+
+    // Receives drops, reconnect attempts and other trouble on the connection
+    pub fn connection_events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.shared.status.subscribe()
+    }
+
+    // Sets the AuthenticateUser payload sent again after every reconnect
+    pub fn set_authenticator<F>(&self, authenticator: F)
+    where
+        F: Fn() -> Value + Send + Sync + 'static,
+    {
+        *self.shared.authenticator.lock().unwrap() = Some(Arc::new(authenticator));
+    }
+
+    // Sets where Authenticate2FA codes come from when signing in again asks for one
+    pub fn set_two_factor_code<F>(&self, two_factor_code: F)
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        *self.shared.two_factor_code.lock().unwrap() = Some(Arc::new(two_factor_code));
+    }
+
+    // Sends a frame without waiting for a reply
+    pub fn send(&self, frame: &Frame) -> Result<(), NdaxError> {
+        if *self.shared.state.borrow() != ConnectionState::Connected {
+            return Err(NdaxError::ConnectionClosed);
+        }
+        self.outbound
+            .send(Message::Text(frame.encode()))
+            .map_err(|_| NdaxError::ConnectionClosed)
+    }
+
+    // Sends a request and waits for the matching reply payload
+    pub async fn call(&self, function_name: &str, payload: Value) -> Result<Value, NdaxError> {
+        self.call_with_timeout(function_name, payload, self.call_timeout)
+            .await
+    }
+
+    pub async fn call_with_timeout(
+        &self,
+        function_name: &str,
+        payload: Value,
+        call_timeout: Duration,
+    ) -> Result<Value, NdaxError> {
+        let sequence = self.next_sequence();
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.shared
+            .pending
+            .lock()
+            .unwrap()
+            .insert(sequence, reply_tx);
+
+        let frame = Frame::request(sequence, function_name, payload);
+        if let Err(e) = self.send(&frame) {
+            self.shared.pending.lock().unwrap().remove(&sequence);
+            return Err(e);
+        }
+
+        match timeout(call_timeout, reply_rx).await {
+            Ok(Ok(reply)) => reply_result(function_name, reply),
+            Ok(Err(_)) => Err(NdaxError::ConnectionClosed),
+            Err(_) => {
+                self.shared.pending.lock().unwrap().remove(&sequence);
+                Err(NdaxError::Timeout {
+                    function_name: function_name.to_string(),
+                    timeout: call_timeout,
+                })
+            }
+        }
+    }
+
+    // Calls a Subscribe* function and remembers it so it is replayed after a reconnect
+    pub async fn subscribe(&self, function_name: &str, payload: Value) -> Result<Value, NdaxError> {
+        let reply = self.call(function_name, payload.clone()).await?;
+        self.shared.record_subscription(Subscription {
+            function_name: function_name.to_string(),
+            payload,
+        });
+        Ok(reply)
+    }
+
+    // Calls an Unsubscribe* function and stops replaying the matching subscription
+    pub async fn unsubscribe(
+        &self,
+        function_name: &str,
+        payload: Value,
+    ) -> Result<Value, NdaxError> {
+        self.shared.remove_subscription(function_name, &payload);
+        self.call(function_name, payload).await
+    }
+
+    // Re-authenticates and replays subscriptions on a fresh connection
+    async fn restore_session(&self) {
+        if let Err(e) = self.reauthenticate().await {
+            // Account subscriptions would only be refused on a session that is not
+            // signed in, so nothing is replayed until the next reconnect
+            self.shared.report(ConnectionEvent::RestoreFailed(format!(
+                "re-authenticating, not resubscribing: {}",
+                e
+            )));
+            return;
+        }
+
+        for subscription in self.shared.replay_order() {
+            if let Err(e) = self
+                .call(&subscription.function_name, subscription.payload)
+                .await
+            {
+                self.shared.report(ConnectionEvent::RestoreFailed(format!(
+                    "resubscribing to {}: {}",
+                    subscription.function_name, e
+                )));
+            }
+        }
+    }
+
+    // Sends the authenticator's AuthenticateUser, if any, answers a 2FA challenge
+    // and checks the gateway signed us in
+    async fn reauthenticate(&self) -> Result<(), NdaxError> {
+        let authenticator = self.shared.authenticator.lock().unwrap().clone();
+        let Some(authenticator) = authenticator else {
+            return Ok(());
+        };
+        let reply = self
+            .call(constants::AUTHENTICATE_USER_PATH_URL, authenticator())
+            .await?;
+        let mut response: AuthenticateResponse = serde_json::from_value(reply)?;
+        if response.requires_2fa {
+            let two_factor_code = self.shared.two_factor_code.lock().unwrap().clone();
+            let Some(two_factor_code) = two_factor_code else {
+                return Err(NdaxError::Authentication(
+                    "account requires 2FA but no code provider is set".to_string(),
+                ));
+            };
+            let reply = self
+                .call(
+                    constants::AUTHENTICATE_2FA_PATH_URL,
+                    json!({ "Code": two_factor_code() }),
+                )
+                .await?;
+            response = serde_json::from_value(reply)?;
+        }
+        check_authenticated(response)?;
+        Ok(())
+    }
+}
+
+// Turns a reply frame into its payload, or the error the gateway reported
+fn reply_result(function_name: &str, reply: Frame) -> Result<Value, NdaxError> {
+    let failed = reply.message_type == MessageType::Error
+        || reply.payload.get("result") == Some(&Value::Bool(false));
+    if !failed {
+        return Ok(reply.payload);
+    }
+    match serde_json::from_value::<GenericResponse>(reply.payload.clone()) {
+        Ok(response) => Err(response.into()),
+        Err(_) => Err(NdaxError::MalformedMessage(format!(
+            "{} failed: {}",
+            function_name, reply.payload
+        ))),
+    }
+}
+
+// Owns the socket for the lifetime of the client, reconnecting whenever it drops
+async fn supervise(
+    url: Url,
+    policy: ReconnectPolicy,
+    ws_stream: WsStream,
+    client: WsClient,
+    mut outbound_rx: mpsc::UnboundedReceiver<Message>,
+) {
+    let shared = Arc::clone(&client.shared);
+    // Only a weak handle is kept so the supervisor stops once every client is dropped
+    let outbound = client.outbound.downgrade();
+    let call_timeout = client.call_timeout;
+    drop(client);
+
+    let mut ws_stream = Some(ws_stream);
+    let mut attempt = 0;
+    loop {
+        if let Some(stream) = ws_stream.take() {
+            attempt = 0;
+            let _ = shared.state.send(ConnectionState::Connected);
+
+            let restore = outbound.upgrade().map(|outbound| {
+                let client = WsClient {
+                    outbound,
+                    shared: Arc::clone(&shared),
+                    call_timeout,
+                };
+                tokio::spawn(async move { client.restore_session().await })
+            });
+
+            let disconnect = serve_connection(stream, &mut outbound_rx, &shared, &policy).await;
+
+            if let Some(restore) = restore {
+                restore.abort();
+            }
+            let _ = shared.state.send(ConnectionState::Reconnecting);
+            // Dropping the senders wakes every caller still waiting on a reply
+            shared.pending.lock().unwrap().clear();
+            while outbound_rx.try_recv().is_ok() {}
+
+            match disconnect {
+                Disconnect::Shutdown => return,
+                Disconnect::Dropped(reason) => shared.report(ConnectionEvent::Dropped(reason)),
+            }
+        }
+
+        let delay = policy.backoff(attempt);
+        attempt = attempt.saturating_add(1);
+        shared.report(ConnectionEvent::Reconnecting { attempt, delay });
+        sleep(delay).await;
+
+        if outbound.upgrade().is_none() {
+            return;
+        }
+        match connect_async(url.clone()).await {
+            Ok((stream, _response)) => ws_stream = Some(stream),
+            Err(e) => shared.report(ConnectionEvent::ReconnectFailed(e.to_string())),
+        }
+    }
+}
+
+// Forwards outgoing messages and dispatches incoming frames until the connection drops
+async fn serve_connection(
+    ws_stream: WsStream,
+    outbound_rx: &mut mpsc::UnboundedReceiver<Message>,
+    shared: &Shared,
+    policy: &ReconnectPolicy,
+) -> Disconnect {
+    let (mut write, mut read) = ws_stream.split();
+    let mut heartbeat = interval(policy.heartbeat_interval);
+    heartbeat.tick().await;
+    let mut last_received = Instant::now();
+
+    loop {
+        tokio::select! {
+            outgoing = outbound_rx.recv() => {
+                let Some(message) = outgoing else {
+                    let _ = write.send(Message::Close(None)).await;
+                    return Disconnect::Shutdown;
+                };
+                if let Err(e) = write.send(message).await {
+                    return Disconnect::Dropped(format!("error sending message: {}", e));
+                }
+            }
+            _ = heartbeat.tick() => {
+                if last_received.elapsed() > policy.heartbeat_timeout {
+                    return Disconnect::Dropped(format!(
+                        "no message received for {:?}",
+                        last_received.elapsed()
+                    ));
+                }
+                let ping = Frame::request(shared.next_sequence(), constants::PING, Value::Null);
+                if let Err(e) = write.send(Message::Text(ping.encode())).await {
+                    return Disconnect::Dropped(format!("error sending ping: {}", e));
+                }
+            }
+            incoming = read.next() => {
+                last_received = Instant::now();
+                match incoming {
+                    Some(Ok(Message::Text(text))) => match Frame::decode(&text) {
+                        Ok(frame) => shared.dispatch(frame),
+                        Err(e) => shared.report(ConnectionEvent::UndecodableFrame(e.to_string())),
+                    },
+                    Some(Ok(Message::Ping(ping))) => {
+                        if let Err(e) = write.send(Message::Pong(ping)).await {
+                            return Disconnect::Dropped(format!("error sending pong: {}", e));
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) => {
+                        return Disconnect::Dropped("closed by the gateway".to_string());
+                    }
+                    Some(Ok(_)) => (),
+                    Some(Err(e)) => return Disconnect::Dropped(format!("WebSocket error: {}", e)),
+                    None => return Disconnect::Dropped("stream ended".to_string()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_dispatch_resolves_matching_request() {
+        let shared = Shared::new();
+        let mut events_rx = shared.events.subscribe();
+        let (reply_tx, mut reply_rx) = oneshot::channel();
+        shared.pending.lock().unwrap().insert(2, reply_tx);
+
+        let other = Frame::new(MessageType::Reply, 3, "Ping", json!({"msg": "PONG"}));
+        shared.dispatch(other);
+        assert!(reply_rx.try_recv().is_err());
+
+        let reply = Frame::new(MessageType::Reply, 2, "GetL2Snapshot", json!([[1, 2, 3]]));
+        shared.dispatch(reply.clone());
+        assert_eq!(reply_rx.try_recv().unwrap(), reply);
+        assert!(shared.pending.lock().unwrap().is_empty());
+
+        // Both frames are still published as events
+        assert_eq!(events_rx.try_recv().unwrap().sequence, 3);
+        assert_eq!(events_rx.try_recv().unwrap(), reply);
+    }
+
+    #[test]
+    fn test_events_do_not_resolve_requests() {
+        let shared = Shared::new();
+        let (reply_tx, mut reply_rx) = oneshot::channel();
+        shared.pending.lock().unwrap().insert(5, reply_tx);
+
+        let event = Frame::new(MessageType::Event, 5, "Level2UpdateEvent", json!([]));
+        shared.dispatch(event);
+        assert!(reply_rx.try_recv().is_err());
+        assert_eq!(shared.pending.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_failed_reply_is_an_error() {
+        let reply = Frame::new(
+            MessageType::Reply,
+            4,
+            "SendOrder",
+            json!({"result": false, "errormsg": "Invalid Request", "errorcode": 100, "detail": null}),
+        );
+        assert!(matches!(
+            reply_result("SendOrder", reply),
+            Err(NdaxError::Exchange { code: 100, .. })
+        ));
+
+        let reply = Frame::new(MessageType::Reply, 5, "Ping", json!({"msg": "PONG"}));
+        assert_eq!(reply_result("Ping", reply).unwrap(), json!({"msg": "PONG"}));
+    }
+
+    #[test]
+    fn test_unsubscribe_forgets_matching_subscription() {
+        let shared = Shared::new();
+        for instrument_id in [1, 90] {
+            shared.record_subscription(Subscription {
+                function_name: "SubscribeTrades".to_string(),
+                payload: json!({"OMSId": 1, "InstrumentId": instrument_id, "IncludeLastCount": 10}),
+            });
+        }
+        // Recording the same subscription twice must not replay it twice
+        shared.record_subscription(Subscription {
+            function_name: "SubscribeTrades".to_string(),
+            payload: json!({"OMSId": 1, "InstrumentId": 1, "IncludeLastCount": 10}),
+        });
+        assert_eq!(shared.subscriptions.lock().unwrap().len(), 2);
+
+        shared.remove_subscription("UnsubscribeTrades", &json!({"OMSId": 1, "InstrumentId": 1}));
+        let subscriptions = shared.subscriptions.lock().unwrap();
+        assert_eq!(subscriptions.len(), 1);
+        assert_eq!(subscriptions[0].payload["InstrumentId"], json!(90));
+    }
+
+    #[test]
+    fn test_replays_account_events_first() {
+        let shared = Shared::new();
+        for function_name in [
+            "SubscribeTrades",
+            "SubscribeLevel2",
+            "SubscribeAccountEvents",
+        ] {
+            shared.record_subscription(Subscription {
+                function_name: function_name.to_string(),
+                payload: json!({"OMSId": 1}),
+            });
+        }
+        let replayed: Vec<String> = shared
+            .replay_order()
+            .into_iter()
+            .map(|s| s.function_name)
+            .collect();
+        assert_eq!(
+            replayed,
+            [
+                "SubscribeAccountEvents",
+                "SubscribeTrades",
+                "SubscribeLevel2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = ReconnectPolicy::default();
+        let first = policy.backoff(0);
+        assert!(first >= policy.initial_backoff / 2 && first <= policy.initial_backoff);
+        for attempt in [10, 20, u32::MAX] {
+            assert!(policy.backoff(attempt) <= policy.max_backoff);
+        }
+    }
+}
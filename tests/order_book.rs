@@ -0,0 +1,49 @@
+use api_networking::frame::Frame;
+use api_networking::order_book::BookState;
+use api_networking::order_book_registry::OrderBookRegistry;
+use rust_decimal::Decimal;
+use serde_json::json;
+
+fn to_frame(value: serde_json::Value) -> Frame {
+    Frame::decode(&value.to_string()).unwrap()
+}
+
+#[test]
+fn registry_tracks_book_from_gateway_frames() {
+    let registry = OrderBookRegistry::new(5);
+    registry.add_instrument(1);
+
+    let snapshot = to_frame(json!({
+        "i": 2,
+        "m": 1,
+        "n": "SubscribeLevel2",
+        "o": "[[1,1,1718003785385,0,5711.70000,1,5711.80000,1,8.13439401,1],
+              [2,1,1718003785385,0,5711.70000,1,5711.70000,1,0.00749800,0]]"
+    }));
+    assert!(registry.handle_frame(&snapshot).is_empty());
+
+    let update = to_frame(json!({
+        "i": 3,
+        "m": 3,
+        "n": "Level2UpdateEvent",
+        "o": "[[3,0,1718007168597,2,5711.70000,0,5711.70000,1,0.00000000,0],
+              [4,1,1718007168597,0,5711.70000,1,5711.60000,1,1.50000000,0]]"
+    }));
+    assert!(registry.handle_frame(&update).is_empty());
+
+    let book = registry.snapshot(1).unwrap();
+    assert_eq!(book.state(), BookState::Synced);
+    assert_eq!(book.best_bid().unwrap().price(), Decimal::new(57116, 1));
+    assert_eq!(book.best_bid().unwrap().volume(), Decimal::new(15, 1));
+    assert_eq!(book.best_ask().unwrap().price(), Decimal::new(57118, 1));
+
+    // A jump in MDUpdateId asks the caller to resync the book
+    let gap = to_frame(json!({
+        "i": 4,
+        "m": 3,
+        "n": "Level2UpdateEvent",
+        "o": "[[9,1,1718007169610,0,5711.70000,1,5711.50000,1,2.00000000,0]]"
+    }));
+    assert_eq!(registry.handle_frame(&gap), vec![1]);
+    assert_eq!(registry.state(1), Some(BookState::Resyncing));
+}